@@ -1,7 +1,22 @@
+pub mod utils;
 pub mod years;
 use crate::years::y2022::mods::day09::{part_one, part_two};
 
 fn main() {
-    let _ = part_one();
-    let _ = part_two();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let command = match args.first().map(String::as_str) {
+        Some("intcode") => years::y2019::intcode::cli::run,
+        Some("circuit") => years::y2015::circuit::run,
+        Some("disk") => years::y2024::mods::day09::run,
+        Some("patrol") => years::y2024::mods::day06::run,
+        _ => {
+            let _ = part_one();
+            let _ = part_two();
+            return;
+        }
+    };
+    if let Err(error) = command(&args[1..]) {
+        eprintln!("{error}");
+        std::process::exit(1);
+    }
 }
@@ -0,0 +1,68 @@
+use crate::utils::bigint::BigUint;
+use std::fmt;
+
+/// A puzzle answer, whatever width it needs
+///
+/// Solvers return the narrowest exact form they have; printing goes
+/// through `Display` so a `Big` answer looks the same as a small one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Answer {
+    Signed(i128),
+    Unsigned(u128),
+    Big(BigUint),
+    Text(String),
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Answer::Signed(value) => write!(f, "{value}"),
+            Answer::Unsigned(value) => write!(f, "{value}"),
+            Answer::Big(value) => write!(f, "{value}"),
+            Answer::Text(text) => write!(f, "{text}"),
+        }
+    }
+}
+
+macro_rules! impl_from {
+    ($variant:ident, $wide:ty, $($t:ty),*) => {
+        $(
+            impl From<$t> for Answer {
+                fn from(value: $t) -> Self {
+                    Answer::$variant(<$wide>::from(value))
+                }
+            }
+        )*
+    };
+}
+
+impl_from!(Signed, i128, i8, i16, i32, i64, i128);
+impl_from!(Unsigned, u128, u8, u16, u32, u64, u128);
+
+impl From<usize> for Answer {
+    fn from(value: usize) -> Self {
+        Answer::Unsigned(value as u128)
+    }
+}
+
+impl From<BigUint> for Answer {
+    /// Narrows to `Unsigned` when the value fits, so equal answers compare equal
+    fn from(value: BigUint) -> Self {
+        match value.to_u128() {
+            Some(value) => Answer::Unsigned(value),
+            None => Answer::Big(value),
+        }
+    }
+}
+
+impl From<String> for Answer {
+    fn from(text: String) -> Self {
+        Answer::Text(text)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(text: &str) -> Self {
+        Answer::Text(text.to_string())
+    }
+}
@@ -0,0 +1,237 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+
+/// Named integer registers, all of which read as `0` until written
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Registers {
+    values: BTreeMap<String, i64>,
+}
+
+impl Registers {
+    pub fn get(&self, name: &str) -> i64 {
+        self.values.get(name).copied().unwrap_or(0)
+    }
+
+    pub fn set(&mut self, name: &str, value: i64) {
+        *self.get_mut(name) = value;
+    }
+
+    pub fn get_mut(&mut self, name: &str) -> &mut i64 {
+        if !self.values.contains_key(name) {
+            self.values.insert(name.to_string(), 0);
+        }
+        self.values.get_mut(name).expect("inserted above")
+    }
+
+    /// Every register touched so far, in name order
+    pub fn iter(&self) -> impl Iterator<Item = (&str, i64)> {
+        self.values
+            .iter()
+            .map(|(name, value)| (name.as_str(), *value))
+    }
+}
+
+/// Where the program counter goes after an instruction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flow {
+    Next,
+    /// Relative jump, `Jump(1)` is the same as `Next`
+    Jump(i64),
+    Goto(usize),
+    Halt,
+}
+
+/// One opcode's semantics, implemented by each puzzle's instruction enum
+pub trait Instruction: fmt::Debug {
+    fn execute(&self, registers: &mut Registers) -> Flow;
+}
+
+/// What counts as the machine going round in circles
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoopDetection {
+    Off,
+    /// Revisiting any instruction, enough for programs without conditional jumps
+    Pc,
+    /// Revisiting an instruction with exactly the same registers
+    State,
+}
+
+/// Why [`Machine::run`] stopped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Exit {
+    /// The program counter left the program, normally by running off the end
+    Terminated {
+        pc: i64,
+    },
+    Halted {
+        pc: usize,
+    },
+    /// The instruction at `pc` was about to run a second time
+    Loop {
+        pc: usize,
+    },
+    StepLimit,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceEntry {
+    pub step: usize,
+    pub pc: usize,
+    pub instruction: String,
+}
+
+/// Runs a program of `I` over a set of named registers
+#[derive(Debug, Clone)]
+pub struct Machine<I> {
+    program: Vec<I>,
+    pub registers: Registers,
+    pc: i64,
+    steps: usize,
+    step_limit: Option<usize>,
+    loop_detection: LoopDetection,
+    seen_pcs: HashSet<usize>,
+    seen_states: HashSet<(usize, Registers)>,
+    trace: Option<Vec<TraceEntry>>,
+}
+
+impl<I: Instruction> Machine<I> {
+    pub fn new(program: Vec<I>) -> Self {
+        Self {
+            program,
+            registers: Registers::default(),
+            pc: 0,
+            steps: 0,
+            step_limit: None,
+            loop_detection: LoopDetection::Off,
+            seen_pcs: HashSet::new(),
+            seen_states: HashSet::new(),
+            trace: None,
+        }
+    }
+
+    pub fn with_step_limit(mut self, limit: usize) -> Self {
+        self.step_limit = Some(limit);
+        self
+    }
+
+    pub fn with_loop_detection(mut self, loop_detection: LoopDetection) -> Self {
+        self.loop_detection = loop_detection;
+        self
+    }
+
+    pub fn with_trace(mut self) -> Self {
+        self.trace = Some(Vec::new());
+        self
+    }
+
+    pub fn program(&self) -> &[I] {
+        &self.program
+    }
+
+    pub fn pc(&self) -> i64 {
+        self.pc
+    }
+
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    pub fn trace(&self) -> &[TraceEntry] {
+        self.trace.as_deref().unwrap_or_default()
+    }
+
+    /// Executes one instruction, or says why it can't
+    pub fn step(&mut self) -> Option<Exit> {
+        let pc = match usize::try_from(self.pc) {
+            Ok(pc) if pc < self.program.len() => pc,
+            _ => return Some(Exit::Terminated { pc: self.pc }),
+        };
+        if self.step_limit.is_some_and(|limit| self.steps >= limit) {
+            return Some(Exit::StepLimit);
+        }
+        let repeated = match self.loop_detection {
+            LoopDetection::Off => false,
+            LoopDetection::Pc => !self.seen_pcs.insert(pc),
+            LoopDetection::State => !self.seen_states.insert((pc, self.registers.clone())),
+        };
+        if repeated {
+            return Some(Exit::Loop { pc });
+        }
+
+        let instruction = &self.program[pc];
+        if let Some(trace) = self.trace.as_mut() {
+            trace.push(TraceEntry {
+                step: self.steps,
+                pc,
+                instruction: format!("{instruction:?}"),
+            });
+        }
+        self.steps += 1;
+        match instruction.execute(&mut self.registers) {
+            Flow::Next => self.pc += 1,
+            Flow::Jump(offset) => self.pc += offset,
+            Flow::Goto(target) => self.pc = target as i64,
+            Flow::Halt => return Some(Exit::Halted { pc }),
+        }
+        None
+    }
+
+    pub fn run(&mut self) -> Exit {
+        loop {
+            if let Some(exit) = self.step() {
+                return exit;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug)]
+    enum Op {
+        Inc(&'static str),
+        Jnz(&'static str, i64),
+        Dec(&'static str),
+    }
+
+    impl Instruction for Op {
+        fn execute(&self, registers: &mut Registers) -> Flow {
+            match self {
+                Op::Inc(reg) => *registers.get_mut(reg) += 1,
+                Op::Dec(reg) => *registers.get_mut(reg) -= 1,
+                Op::Jnz(reg, offset) if registers.get(reg) != 0 => return Flow::Jump(*offset),
+                Op::Jnz(..) => (),
+            }
+            Flow::Next
+        }
+    }
+
+    #[test]
+    fn runs_loops_until_terminated() {
+        // moves a into b, three times round the loop
+        let program = vec![Op::Inc("b"), Op::Dec("a"), Op::Jnz("a", -2)];
+        let mut machine = Machine::new(program).with_trace();
+        machine.registers.set("a", 3);
+        assert_eq!(machine.run(), Exit::Terminated { pc: 3 });
+        assert_eq!(machine.registers.get("b"), 3);
+        assert_eq!(machine.steps(), 9);
+        assert_eq!(machine.trace()[3].pc, 0);
+    }
+
+    #[test]
+    fn detects_loops_and_step_limits() {
+        let program = vec![Op::Inc("a"), Op::Jnz("a", -1)];
+        let mut by_pc = Machine::new(program).with_loop_detection(LoopDetection::Pc);
+        assert_eq!(by_pc.run(), Exit::Loop { pc: 0 });
+        assert_eq!(by_pc.registers.get("a"), 1);
+
+        let program = vec![Op::Inc("a"), Op::Jnz("a", -1)];
+        let mut limited = Machine::new(program)
+            .with_loop_detection(LoopDetection::State)
+            .with_step_limit(10);
+        assert_eq!(limited.run(), Exit::StepLimit);
+        assert_eq!(limited.registers.get("a"), 5);
+    }
+}
@@ -0,0 +1,278 @@
+use crate::utils::error::AocError;
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Mul, Rem};
+use std::str::FromStr;
+
+/// Arbitrary-precision non-negative integer
+///
+/// Stored as little-endian base 2^32 limbs with no trailing zero limbs,
+/// so zero is an empty vector and derived equality is value equality.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct BigUint {
+    limbs: Vec<u32>,
+}
+
+impl BigUint {
+    pub fn zero() -> Self {
+        Self { limbs: Vec::new() }
+    }
+
+    pub fn one() -> Self {
+        Self::from(1_u32)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    fn trimmed(mut limbs: Vec<u32>) -> Self {
+        while limbs.last() == Some(&0) {
+            limbs.pop();
+        }
+        Self { limbs }
+    }
+
+    pub fn bits(&self) -> u64 {
+        match self.limbs.last() {
+            Some(top) => self.limbs.len() as u64 * 32 - u64::from(top.leading_zeros()),
+            None => 0,
+        }
+    }
+
+    fn bit(&self, index: u64) -> bool {
+        let limb = (index / 32) as usize;
+        limb < self.limbs.len() && (self.limbs[limb] >> (index % 32)) & 1 == 1
+    }
+
+    /// `self - other`, `None` if that would be negative
+    pub fn checked_sub(&self, other: &Self) -> Option<Self> {
+        if *self < *other {
+            return None;
+        }
+        let mut limbs = self.limbs.clone();
+        let mut borrow = 0_i64;
+        for (i, limb) in limbs.iter_mut().enumerate() {
+            let value = i64::from(*limb) - i64::from(*other.limbs.get(i).unwrap_or(&0)) - borrow;
+            borrow = i64::from(value < 0);
+            *limb = value.rem_euclid(1 << 32) as u32;
+        }
+        Some(Self::trimmed(limbs))
+    }
+
+    /// Quotient and remainder for a small divisor, `None` when dividing by zero
+    pub fn div_rem_small(&self, divisor: u32) -> Option<(Self, u32)> {
+        if divisor == 0 {
+            return None;
+        }
+        let mut quotient = vec![0; self.limbs.len()];
+        let mut remainder = 0_u64;
+        for (i, limb) in self.limbs.iter().enumerate().rev() {
+            let current = (remainder << 32) | u64::from(*limb);
+            quotient[i] = (current / u64::from(divisor)) as u32;
+            remainder = current % u64::from(divisor);
+        }
+        Some((Self::trimmed(quotient), remainder as u32))
+    }
+
+    /// Remainder by shift-and-subtract, `None` when dividing by zero
+    pub fn checked_rem(&self, modulus: &Self) -> Option<Self> {
+        if modulus.is_zero() {
+            return None;
+        }
+        if self < modulus {
+            return Some(self.clone());
+        }
+        let mut remainder = Self::zero();
+        for index in (0..self.bits()).rev() {
+            remainder = &remainder + &remainder;
+            if self.bit(index) {
+                remainder = &remainder + &Self::one();
+            }
+            if remainder >= *modulus {
+                remainder = remainder.checked_sub(modulus).expect("checked above");
+            }
+        }
+        Some(remainder)
+    }
+
+    /// The value as a `u128`, `None` if it is too big
+    pub fn to_u128(&self) -> Option<u128> {
+        if self.limbs.len() > 4 {
+            return None;
+        }
+        Some(
+            self.limbs
+                .iter()
+                .rev()
+                .fold(0, |value, limb| (value << 32) | u128::from(*limb)),
+        )
+    }
+}
+
+impl From<u128> for BigUint {
+    fn from(mut value: u128) -> Self {
+        let mut limbs = Vec::new();
+        while value > 0 {
+            limbs.push(value as u32);
+            value >>= 32;
+        }
+        Self { limbs }
+    }
+}
+
+impl From<u64> for BigUint {
+    fn from(value: u64) -> Self {
+        Self::from(u128::from(value))
+    }
+}
+
+impl From<u32> for BigUint {
+    fn from(value: u32) -> Self {
+        Self::from(u128::from(value))
+    }
+}
+
+impl Ord for BigUint {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.limbs
+            .len()
+            .cmp(&other.limbs.len())
+            .then_with(|| self.limbs.iter().rev().cmp(other.limbs.iter().rev()))
+    }
+}
+
+impl PartialOrd for BigUint {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Add for &BigUint {
+    type Output = BigUint;
+
+    fn add(self, other: &BigUint) -> BigUint {
+        let mut limbs = Vec::with_capacity(self.limbs.len().max(other.limbs.len()) + 1);
+        let mut carry = 0_u64;
+        for i in 0..self.limbs.len().max(other.limbs.len()) {
+            let sum = u64::from(*self.limbs.get(i).unwrap_or(&0))
+                + u64::from(*other.limbs.get(i).unwrap_or(&0))
+                + carry;
+            limbs.push(sum as u32);
+            carry = sum >> 32;
+        }
+        limbs.push(carry as u32);
+        BigUint::trimmed(limbs)
+    }
+}
+
+impl Add for BigUint {
+    type Output = BigUint;
+
+    fn add(self, other: BigUint) -> BigUint {
+        &self + &other
+    }
+}
+
+impl Mul for &BigUint {
+    type Output = BigUint;
+
+    fn mul(self, other: &BigUint) -> BigUint {
+        let mut limbs = vec![0_u32; self.limbs.len() + other.limbs.len()];
+        for (i, a) in self.limbs.iter().enumerate() {
+            let mut carry = 0_u64;
+            for (j, b) in other.limbs.iter().enumerate() {
+                let current = u64::from(limbs[i + j]) + u64::from(*a) * u64::from(*b) + carry;
+                limbs[i + j] = current as u32;
+                carry = current >> 32;
+            }
+            limbs[i + other.limbs.len()] = carry as u32;
+        }
+        BigUint::trimmed(limbs)
+    }
+}
+
+impl Mul for BigUint {
+    type Output = BigUint;
+
+    fn mul(self, other: BigUint) -> BigUint {
+        &self * &other
+    }
+}
+
+impl Rem for &BigUint {
+    type Output = BigUint;
+
+    /// Panics on a zero modulus, like the built-in integers
+    fn rem(self, modulus: &BigUint) -> BigUint {
+        self.checked_rem(modulus)
+            .expect("attempt to calculate the remainder with a divisor of zero")
+    }
+}
+
+impl std::iter::Sum for BigUint {
+    fn sum<I: Iterator<Item = BigUint>>(iter: I) -> Self {
+        iter.fold(BigUint::zero(), |total, value| &total + &value)
+    }
+}
+
+impl fmt::Display for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const CHUNK: u32 = 1_000_000_000;
+        if self.is_zero() {
+            return f.pad("0");
+        }
+        // nine decimal digits at a time, least significant first
+        let mut chunks = Vec::new();
+        let mut rest = self.clone();
+        while !rest.is_zero() {
+            let (quotient, remainder) = rest.div_rem_small(CHUNK).expect("non-zero divisor");
+            chunks.push(remainder);
+            rest = quotient;
+        }
+        let mut text = chunks.pop().expect("non-zero value").to_string();
+        for chunk in chunks.iter().rev() {
+            text.push_str(&format!("{chunk:09}"));
+        }
+        f.pad(&text)
+    }
+}
+
+impl FromStr for BigUint {
+    type Err = AocError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        if text.is_empty() || !text.bytes().all(|byte| byte.is_ascii_digit()) {
+            return Err(AocError::parse(text, "expected decimal digits"));
+        }
+        let ten = BigUint::from(10_u32);
+        Ok(text.bytes().fold(BigUint::zero(), |value, digit| {
+            &(&value * &ten) + &BigUint::from(u32::from(digit - b'0'))
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn does_arithmetic_past_u128() {
+        let max = BigUint::from(u128::MAX);
+        let square = &max * &max;
+        assert_eq!(
+            square.to_string(),
+            "115792089237316195423570985008687907852589419931798687112530834793049593217025"
+        );
+        assert_eq!(square.to_string().parse::<BigUint>().unwrap(), square);
+        assert_eq!(square.to_u128(), None);
+        assert_eq!((&max + &BigUint::one()).bits(), 129);
+        assert_eq!(
+            (&square % &BigUint::from(1_000_000_007_u64)).to_u128(),
+            Some(u128::MAX % 1_000_000_007 * (u128::MAX % 1_000_000_007) % 1_000_000_007)
+        );
+        assert_eq!(square.checked_sub(&square), Some(BigUint::zero()));
+        assert_eq!(max.checked_sub(&square), None);
+        assert!("12a".parse::<BigUint>().is_err());
+    }
+}
@@ -0,0 +1,181 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// Shape of the sequence `x0, f(x0), f(f(x0)), ...` once it starts repeating
+///
+/// `start` is the index of the first state that is part of the loop and
+/// `length` is how many steps it takes to come back around to it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    pub start: usize,
+    pub length: usize,
+}
+
+impl Cycle {
+    /// Maps any step count onto an equivalent one below `start + length`
+    ///
+    /// e.g. the state after one billion steps equals the state after
+    /// `cycle.reduce(1_000_000_000)` steps.
+    pub fn reduce(&self, steps: usize) -> usize {
+        if steps < self.start {
+            steps
+        } else {
+            self.start + (steps - self.start) % self.length
+        }
+    }
+}
+
+/// Hash-based detection that keeps every state seen up to the repeat
+///
+/// Uses more memory than [`floyd`] or [`brent`] but finds the cycle in a
+/// single pass and can answer [`CycleHistory::state_after`] without replaying.
+#[derive(Debug, Clone)]
+pub struct CycleHistory<S> {
+    pub cycle: Cycle,
+    pub states: Vec<S>,
+}
+
+impl<S> CycleHistory<S> {
+    /// State after `steps` applications of the step function
+    pub fn state_after(&self, steps: usize) -> &S {
+        &self.states[self.cycle.reduce(steps)]
+    }
+}
+
+/// Runs `step` from `initial` until a state repeats, remembering all of them
+pub fn find_cycle<S, F>(initial: S, mut step: F) -> CycleHistory<S>
+where
+    S: Clone + Eq + Hash,
+    F: FnMut(&S) -> S,
+{
+    let mut seen: HashMap<S, usize> = HashMap::new();
+    let mut states: Vec<S> = Vec::new();
+    let mut current = initial;
+
+    loop {
+        if let Some(&start) = seen.get(&current) {
+            let length = states.len() - start;
+            return CycleHistory {
+                cycle: Cycle { start, length },
+                states,
+            };
+        }
+        let next = step(&current);
+        seen.insert(current.clone(), states.len());
+        states.push(current);
+        current = next;
+    }
+}
+
+/// Floyd's tortoise and hare, only ever holding a few states in memory
+pub fn floyd<S, F>(initial: S, step: F) -> Cycle
+where
+    S: Clone + Eq,
+    F: Fn(&S) -> S,
+{
+    // find a meeting point somewhere inside the loop
+    let mut tortoise = step(&initial);
+    let mut hare = step(&step(&initial));
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&step(&hare));
+    }
+
+    // distance from the start to the loop equals distance from the meeting point
+    let mut start = 0;
+    tortoise = initial;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        start += 1;
+    }
+
+    let mut length = 1;
+    hare = step(&tortoise);
+    while tortoise != hare {
+        hare = step(&hare);
+        length += 1;
+    }
+
+    Cycle { start, length }
+}
+
+/// Brent's algorithm, usually fewer step calls than [`floyd`]
+pub fn brent<S, F>(initial: S, step: F) -> Cycle
+where
+    S: Clone + Eq,
+    F: Fn(&S) -> S,
+{
+    // find the loop length by teleporting the tortoise at powers of two
+    let mut power = 1;
+    let mut length = 1;
+    let mut tortoise = initial.clone();
+    let mut hare = step(&initial);
+    while tortoise != hare {
+        if power == length {
+            tortoise = hare.clone();
+            power *= 2;
+            length = 0;
+        }
+        hare = step(&hare);
+        length += 1;
+    }
+
+    // walk two pointers `length` apart until they meet at the loop start
+    let mut start = 0;
+    tortoise = initial.clone();
+    hare = initial;
+    for _ in 0..length {
+        hare = step(&hare);
+    }
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        start += 1;
+    }
+
+    Cycle { start, length }
+}
+
+/// State after `steps` applications, replaying at most `start + length` of them
+///
+/// Pairs with [`floyd`] and [`brent`] when the states were not kept around.
+pub fn state_after<S, F>(initial: S, step: F, cycle: Cycle, steps: usize) -> S
+where
+    F: Fn(&S) -> S,
+{
+    (0..cycle.reduce(steps)).fold(initial, |state, _| step(&state))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 0 -> 1 -> 2 -> 3 -> 4 -> 2 -> ...
+    fn step(x: &u32) -> u32 {
+        if *x == 4 {
+            2
+        } else {
+            x + 1
+        }
+    }
+
+    #[test]
+    fn all_detectors_agree() {
+        let expected = Cycle {
+            start: 2,
+            length: 3,
+        };
+        assert_eq!(find_cycle(0, step).cycle, expected);
+        assert_eq!(floyd(0, step), expected);
+        assert_eq!(brent(0, step), expected);
+    }
+
+    #[test]
+    fn extrapolates_far_ahead() {
+        let history = find_cycle(0, step);
+        assert_eq!(*history.state_after(1), 1);
+        assert_eq!(*history.state_after(1_000_000_000), 4);
+        assert_eq!(state_after(0, step, history.cycle, 1_000_000_000), 4);
+    }
+}
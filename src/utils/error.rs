@@ -0,0 +1,80 @@
+use std::fmt;
+
+/// Crate-wide error for shared utilities
+///
+/// Day modules are free to keep their own small error enums; anything that
+/// goes through `utils` reports back with this.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AocError {
+    /// Input text that did not have the expected shape
+    ///
+    /// `line` is 1-based and filled in by whoever knows which line was being
+    /// parsed, see [`AocError::on_line`].
+    Parse {
+        line: Option<usize>,
+        input: String,
+        reason: String,
+    },
+    /// Pixel letters that matched no known font glyph
+    ///
+    /// `text` holds the partial read with `?` in place of each unknown glyph,
+    /// and `unrecognized` pairs those positions with the glyph drawn in `#`/`.`.
+    UnrecognizedGlyphs {
+        text: String,
+        unrecognized: Vec<(usize, String)>,
+    },
+}
+
+impl AocError {
+    pub fn parse(input: &str, reason: impl Into<String>) -> Self {
+        AocError::Parse {
+            line: None,
+            input: input.to_string(),
+            reason: reason.into(),
+        }
+    }
+
+    /// Attaches a 1-based line number, replacing the input with the whole line
+    pub fn on_line(self, number: usize, text: &str) -> Self {
+        match self {
+            AocError::Parse { reason, input, .. } => AocError::Parse {
+                line: Some(number),
+                input: if text.is_empty() {
+                    input
+                } else {
+                    text.to_string()
+                },
+                reason,
+            },
+            other => other,
+        }
+    }
+}
+
+impl fmt::Display for AocError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AocError::Parse {
+                line: Some(line),
+                input,
+                reason,
+            } => write!(f, "parse error on line {line}: {reason} in {input:?}"),
+            AocError::Parse {
+                line: None,
+                input,
+                reason,
+            } => write!(f, "parse error: {reason} in {input:?}"),
+            AocError::UnrecognizedGlyphs { text, unrecognized } => {
+                write!(f, "unrecognized glyphs in {text:?}")?;
+                for (position, glyph) in unrecognized {
+                    write!(f, "\nat position {position}:\n{glyph}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for AocError {}
+
+pub type AocResult<T> = Result<T, AocError>;
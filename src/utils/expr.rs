@@ -0,0 +1,265 @@
+use crate::utils::error::{AocError, AocResult};
+use std::fmt;
+
+/// A binary operator on `i64` that puzzles can plug into [`evaluate`]
+pub trait Operator: Copy + fmt::Debug {
+    /// How the operator is written, e.g. `+` or `||`
+    fn symbol(&self) -> &'static str;
+
+    /// `None` on overflow or when the operation is undefined
+    fn apply(&self, left: i64, right: i64) -> Option<i64>;
+
+    /// The left operands that give `result` when applied with `right`
+    fn invert(&self, result: i64, right: i64) -> Inverse;
+
+    /// Higher binds tighter under [`Mode::Precedence`]
+    fn precedence(&self) -> u8;
+}
+
+/// Answer to [`Operator::invert`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Inverse {
+    /// No left operand gives the result
+    Impossible,
+    Exactly(i64),
+    /// Every left operand does, as with anything times zero
+    Any,
+}
+
+impl From<Option<i64>> for Inverse {
+    fn from(left: Option<i64>) -> Self {
+        left.map_or(Inverse::Impossible, Inverse::Exactly)
+    }
+}
+
+/// The usual four operators, with integer division that must be exact to invert
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arithmetic {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+}
+
+impl Arithmetic {
+    pub const ALL: [Arithmetic; 4] = [
+        Arithmetic::Add,
+        Arithmetic::Subtract,
+        Arithmetic::Multiply,
+        Arithmetic::Divide,
+    ];
+}
+
+impl Operator for Arithmetic {
+    fn symbol(&self) -> &'static str {
+        match self {
+            Arithmetic::Add => "+",
+            Arithmetic::Subtract => "-",
+            Arithmetic::Multiply => "*",
+            Arithmetic::Divide => "/",
+        }
+    }
+
+    fn apply(&self, left: i64, right: i64) -> Option<i64> {
+        match self {
+            Arithmetic::Add => left.checked_add(right),
+            Arithmetic::Subtract => left.checked_sub(right),
+            Arithmetic::Multiply => left.checked_mul(right),
+            Arithmetic::Divide => left.checked_div(right),
+        }
+    }
+
+    fn invert(&self, result: i64, right: i64) -> Inverse {
+        match self {
+            Arithmetic::Add => result.checked_sub(right).into(),
+            Arithmetic::Subtract => result.checked_add(right).into(),
+            Arithmetic::Multiply if right == 0 && result == 0 => Inverse::Any,
+            Arithmetic::Multiply => (right != 0 && result % right == 0)
+                .then(|| result / right)
+                .into(),
+            Arithmetic::Divide if right == 0 => Inverse::Impossible,
+            // several left operands truncate to the same result, take the exact one
+            Arithmetic::Divide => result.checked_mul(right).into(),
+        }
+    }
+
+    fn precedence(&self) -> u8 {
+        match self {
+            Arithmetic::Add | Arithmetic::Subtract => 1,
+            Arithmetic::Multiply | Arithmetic::Divide => 2,
+        }
+    }
+}
+
+/// How operators without parentheses group
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// Strictly left to right, `2 + 3 * 4` is `20`
+    LeftToRight,
+    /// By [`Operator::precedence`], left to right among equals
+    Precedence,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Token<O> {
+    Number(i64),
+    Operator(O),
+    Open,
+    Close,
+}
+
+/// Splits `text` into numbers, parentheses and any of `operators`
+///
+/// Longer symbols win, so `||` is not read as two `|`. Numbers are
+/// unsigned, there is no unary minus.
+pub fn tokenize<O: Operator>(text: &str, operators: &[O]) -> AocResult<Vec<Token<O>>> {
+    let mut by_length = operators.to_vec();
+    by_length.sort_by_key(|operator| std::cmp::Reverse(operator.symbol().len()));
+
+    let mut tokens = Vec::new();
+    let mut rest = text.trim_start();
+    while let Some(next) = rest.chars().next() {
+        let length = if next.is_ascii_digit() {
+            let digits = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(rest.len());
+            let number = rest[..digits]
+                .parse()
+                .map_err(|_| AocError::parse(text, format!("{} is too large", &rest[..digits])))?;
+            tokens.push(Token::Number(number));
+            digits
+        } else if next == '(' {
+            tokens.push(Token::Open);
+            1
+        } else if next == ')' {
+            tokens.push(Token::Close);
+            1
+        } else {
+            let operator = by_length
+                .iter()
+                .find(|operator| rest.starts_with(operator.symbol()))
+                .ok_or_else(|| AocError::parse(text, format!("unexpected {next:?}")))?;
+            tokens.push(Token::Operator(*operator));
+            operator.symbol().len()
+        };
+        rest = rest[length..].trim_start();
+    }
+    Ok(tokens)
+}
+
+fn reduce<O: Operator>(values: &mut Vec<i64>, operator: O, text: &str) -> AocResult<()> {
+    let (right, left) = (values.pop(), values.pop());
+    let (Some(left), Some(right)) = (left, right) else {
+        return Err(AocError::parse(text, "operator is missing an operand"));
+    };
+    let value = operator.apply(left, right).ok_or_else(|| {
+        AocError::parse(
+            text,
+            format!("{left} {} {right} is out of range", operator.symbol()),
+        )
+    })?;
+    values.push(value);
+    Ok(())
+}
+
+/// Evaluates tokens with the shunting-yard algorithm
+///
+/// `text` is only used for error messages.
+pub fn evaluate_tokens<O: Operator>(tokens: &[Token<O>], mode: Mode, text: &str) -> AocResult<i64> {
+    let binding = |operator: &O| match mode {
+        Mode::LeftToRight => 0,
+        Mode::Precedence => operator.precedence(),
+    };
+    let mut values: Vec<i64> = Vec::new();
+    // `None` marks an open parenthesis
+    let mut pending: Vec<Option<O>> = Vec::new();
+    let mut expect_operand = true;
+
+    for token in tokens {
+        match (token, expect_operand) {
+            (Token::Number(number), true) => {
+                values.push(*number);
+                expect_operand = false;
+            }
+            (Token::Open, true) => pending.push(None),
+            (Token::Operator(operator), false) => {
+                while let Some(Some(top)) = pending.last() {
+                    if binding(top) < binding(operator) {
+                        break;
+                    }
+                    reduce(&mut values, *top, text)?;
+                    pending.pop();
+                }
+                pending.push(Some(*operator));
+                expect_operand = true;
+            }
+            (Token::Close, false) => loop {
+                match pending.pop() {
+                    Some(Some(operator)) => reduce(&mut values, operator, text)?,
+                    Some(None) => break,
+                    None => return Err(AocError::parse(text, "unbalanced `)`")),
+                }
+            },
+            (token, _) => return Err(AocError::parse(text, format!("unexpected {token:?}"))),
+        }
+    }
+    if expect_operand {
+        return Err(AocError::parse(text, "expression ends early"));
+    }
+    while let Some(operator) = pending.pop() {
+        let operator = operator.ok_or_else(|| AocError::parse(text, "unbalanced `(`"))?;
+        reduce(&mut values, operator, text)?;
+    }
+    Ok(values[0])
+}
+
+/// Parses and evaluates `text` in one go
+///
+/// `evaluate("1 + 2 * 3", &Arithmetic::ALL, Mode::Precedence)` is `Ok(7)`.
+pub fn evaluate<O: Operator>(text: &str, operators: &[O], mode: Mode) -> AocResult<i64> {
+    evaluate_tokens(&tokenize(text, operators)?, mode, text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evaluates_in_both_modes() {
+        let text = "2 * 3 + (4 * 5)";
+        assert_eq!(
+            evaluate(text, &Arithmetic::ALL, Mode::LeftToRight).unwrap(),
+            26
+        );
+        assert_eq!(
+            evaluate(text, &Arithmetic::ALL, Mode::Precedence).unwrap(),
+            26
+        );
+        let text = "5 + (8 * 3 + 9 + 3 * 4 * 3)";
+        assert_eq!(
+            evaluate(text, &Arithmetic::ALL, Mode::LeftToRight).unwrap(),
+            437
+        );
+        assert_eq!(
+            evaluate(text, &Arithmetic::ALL, Mode::Precedence).unwrap(),
+            74
+        );
+        assert_eq!(
+            evaluate("10 - 4 - 3", &Arithmetic::ALL, Mode::Precedence).unwrap(),
+            3
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_expressions() {
+        for text in ["1 +", "(1 + 2", "1 + 2)", "1 2", "1 % 2", "* 3"] {
+            assert!(
+                evaluate(text, &Arithmetic::ALL, Mode::Precedence).is_err(),
+                "{text}"
+            );
+        }
+        assert_eq!(Arithmetic::Multiply.invert(12, 4), Inverse::Exactly(3));
+        assert_eq!(Arithmetic::Multiply.invert(0, 0), Inverse::Any);
+        assert_eq!(Arithmetic::Multiply.invert(5, 0), Inverse::Impossible);
+    }
+}
@@ -0,0 +1,294 @@
+use crate::utils::error::{AocError, AocResult};
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Point {
+    pub x: i64,
+    pub y: i64,
+}
+
+impl Point {
+    pub const ORIGIN: Point = Point { x: 0, y: 0 };
+
+    pub fn new(x: i64, y: i64) -> Self {
+        Self { x, y }
+    }
+
+    pub fn manhattan(self, other: Point) -> i64 {
+        (self.x - other.x).abs() + (self.y - other.y).abs()
+    }
+}
+
+/// An axis-aligned stretch of wire, `start` and `end` both included
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Segment {
+    pub start: Point,
+    pub end: Point,
+    /// Steps along the wire before reaching `start`
+    pub steps: i64,
+}
+
+impl Segment {
+    /// Zero-length segments count as horizontal
+    pub fn is_horizontal(&self) -> bool {
+        self.start.y == self.end.y
+    }
+
+    /// Smallest and largest coordinate along the segment's axis
+    fn span(&self) -> (i64, i64) {
+        let (a, b) = if self.is_horizontal() {
+            (self.start.x, self.end.x)
+        } else {
+            (self.start.y, self.end.y)
+        };
+        (a.min(b), a.max(b))
+    }
+
+    /// Steps along the wire to `point`, which must lie on the segment
+    pub fn steps_to(&self, point: Point) -> i64 {
+        self.steps + self.start.manhattan(point)
+    }
+}
+
+/// A wire as the segments it is laid out in, starting at the origin
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Wire {
+    pub segments: Vec<Segment>,
+}
+
+impl Wire {
+    /// Parses moves like `R8,U5,L5,D3`
+    pub fn parse(text: &str) -> AocResult<Self> {
+        let mut segments = Vec::new();
+        let (mut position, mut steps) = (Point::ORIGIN, 0);
+        for step in text.trim().split(',') {
+            let mut chars = step.trim().chars();
+            let (dx, dy) = match chars.next() {
+                Some('R') => (1, 0),
+                Some('L') => (-1, 0),
+                Some('U') => (0, 1),
+                Some('D') => (0, -1),
+                _ => {
+                    return Err(AocError::parse(
+                        step,
+                        "expected a direction of R, L, U or D",
+                    ))
+                }
+            };
+            let length: i64 = chars
+                .as_str()
+                .parse()
+                .map_err(|_| AocError::parse(step, "expected a length"))?;
+            let end = Point::new(position.x + dx * length, position.y + dy * length);
+            segments.push(Segment {
+                start: position,
+                end,
+                steps,
+            });
+            (position, steps) = (end, steps + length);
+        }
+        Ok(Self { segments })
+    }
+}
+
+/// A point where two different wires meet
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Intersection {
+    pub point: Point,
+    /// Indices of the two wires, lower first
+    pub wires: [usize; 2],
+    /// Steps along each wire to `point`, in the same order as `wires`
+    pub steps: [i64; 2],
+}
+
+impl Intersection {
+    fn new(point: Point, (a, first): (usize, &Segment), (b, second): (usize, &Segment)) -> Self {
+        let (a, b, first, second) = if a < b {
+            (a, b, first, second)
+        } else {
+            (b, a, second, first)
+        };
+        Self {
+            point,
+            wires: [a, b],
+            steps: [first.steps_to(point), second.steps_to(point)],
+        }
+    }
+
+    pub fn distance(&self) -> i64 {
+        self.point.manhattan(Point::ORIGIN)
+    }
+
+    pub fn total_steps(&self) -> i64 {
+        self.steps.iter().sum()
+    }
+}
+
+/// Every crossing between segments of different wires, except the origin
+///
+/// All wires start at the origin, so it is never reported. Perpendicular
+/// crossings come from a sweep over `x`: horizontal segments are active
+/// between their ends, and each vertical segment queries the active ones in
+/// its `y` range. Segments lying along each other are reported at both ends
+/// of the shared stretch and at its points nearest the origin, one step to
+/// either side when the stretch runs through it; the smallest distance and
+/// step count always lie at one of those.
+///
+/// A point where a wire passes more than once is reported once per pass;
+/// take the minimum over the results for first-visit step counts.
+pub fn intersections(wires: &[Wire]) -> Vec<Intersection> {
+    let segments: Vec<(usize, &Segment)> = wires
+        .iter()
+        .enumerate()
+        .flat_map(|(wire, w)| w.segments.iter().map(move |segment| (wire, segment)))
+        .collect();
+    let mut found = crossings(&segments);
+    found.extend(overlaps(&segments));
+    found.retain(|crossing| crossing.point != Point::ORIGIN);
+    found
+}
+
+fn crossings(segments: &[(usize, &Segment)]) -> Vec<Intersection> {
+    // at equal `x`, horizontals enter before and leave after the queries
+    const ENTER: u8 = 0;
+    const QUERY: u8 = 1;
+    const LEAVE: u8 = 2;
+
+    let mut events = Vec::new();
+    for (index, (_, segment)) in segments.iter().enumerate() {
+        if segment.is_horizontal() {
+            let (low, high) = segment.span();
+            events.push((low, ENTER, index));
+            events.push((high, LEAVE, index));
+        } else {
+            events.push((segment.start.x, QUERY, index));
+        }
+    }
+    events.sort_unstable();
+
+    let mut found = Vec::new();
+    let mut active: BTreeMap<i64, Vec<usize>> = BTreeMap::new();
+    for (x, kind, index) in events {
+        let y = segments[index].1.start.y;
+        match kind {
+            ENTER => active.entry(y).or_default().push(index),
+            LEAVE => {
+                let row = active.get_mut(&y).expect("entered before leaving");
+                row.retain(|other| *other != index);
+                if row.is_empty() {
+                    active.remove(&y);
+                }
+            }
+            _ => {
+                let vertical = segments[index];
+                let (low, high) = vertical.1.span();
+                for (y, row) in active.range(low..=high) {
+                    let point = Point::new(x, *y);
+                    found.extend(
+                        row.iter()
+                            .map(|other| segments[*other])
+                            .filter(|horizontal| horizontal.0 != vertical.0)
+                            .map(|horizontal| Intersection::new(point, horizontal, vertical)),
+                    );
+                }
+            }
+        }
+    }
+    found
+}
+
+fn overlaps(segments: &[(usize, &Segment)]) -> Vec<Intersection> {
+    // collinear segments share a line: (is horizontal, the fixed coordinate)
+    let mut lines: BTreeMap<(bool, i64), Vec<(usize, &Segment)>> = BTreeMap::new();
+    for &(wire, segment) in segments {
+        let fixed = if segment.is_horizontal() {
+            segment.start.y
+        } else {
+            segment.start.x
+        };
+        lines
+            .entry((segment.is_horizontal(), fixed))
+            .or_default()
+            .push((wire, segment));
+    }
+
+    let mut found = Vec::new();
+    for ((horizontal, fixed), line) in lines {
+        let at = |along: i64| {
+            if horizontal {
+                Point::new(along, fixed)
+            } else {
+                Point::new(fixed, along)
+            }
+        };
+        for (i, first) in line.iter().enumerate() {
+            for second in line[i + 1..].iter().filter(|second| second.0 != first.0) {
+                let (a, b) = (first.1.span(), second.1.span());
+                let (low, high) = (a.0.max(b.0), a.1.min(b.1));
+                if low > high {
+                    continue;
+                }
+                let mut points: Vec<i64> = [low, -1, 0, 1, high]
+                    .iter()
+                    .map(|along| along.clamp(&low, &high))
+                    .copied()
+                    .collect();
+                points.dedup();
+                for along in points {
+                    found.push(Intersection::new(at(along), *first, *second));
+                }
+            }
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wires(text: &str) -> Vec<Wire> {
+        text.lines()
+            .map(|line| Wire::parse(line).unwrap())
+            .collect()
+    }
+
+    fn best(found: &[Intersection]) -> (i64, i64) {
+        (
+            found.iter().map(Intersection::distance).min().unwrap(),
+            found.iter().map(Intersection::total_steps).min().unwrap(),
+        )
+    }
+
+    #[test]
+    fn finds_crossings_between_wires() {
+        let found = intersections(&wires("R8,U5,L5,D3\nU7,R6,D4,L4"));
+        assert_eq!(best(&found), (6, 30));
+        let found = intersections(&wires(
+            "R75,D30,R83,U83,L12,D49,R71,U7,L72\nU62,R66,U55,R34,D71,R55,D58,R83",
+        ));
+        assert_eq!(best(&found), (159, 610));
+
+        // a third wire running along the first one's opening stretch
+        let found = intersections(&wires("R8,U5,L5,D3\nU7,R6,D4,L4\nU0,R3"));
+        assert!(found.contains(&Intersection {
+            point: Point::new(3, 0),
+            wires: [0, 2],
+            steps: [3, 3],
+        }));
+        assert!(found.iter().all(|found| found.point != Point::ORIGIN));
+
+        // both wires leave the origin along the same line
+        let found = intersections(&wires("R5,U2\nR3,D2"));
+        assert_eq!(best(&found), (1, 2));
+        assert!(found.contains(&Intersection {
+            point: Point::new(1, 0),
+            wires: [0, 1],
+            steps: [1, 1],
+        }));
+        // ...and one doubling back through it
+        let found = intersections(&wires("L4,R9\nR2,U1"));
+        assert_eq!(best(&found), (1, 10));
+        assert!(Wire::parse("R8,F2").is_err());
+    }
+}
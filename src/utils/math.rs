@@ -0,0 +1,351 @@
+use std::fmt::Debug;
+
+/// Primitive integer operations needed by the number-theory helpers
+///
+/// Implemented for every built-in signed and unsigned width so the helpers
+/// below can be used with whatever type a puzzle already parses into.
+/// All arithmetic goes through the `checked_*` methods so overflow surfaces
+/// as `None` instead of wrapping or panicking.
+pub trait Integer: Copy + Ord + Debug {
+    fn zero() -> Self;
+    fn one() -> Self;
+    fn checked_add(self, other: Self) -> Option<Self>;
+    fn checked_sub(self, other: Self) -> Option<Self>;
+    fn checked_mul(self, other: Self) -> Option<Self>;
+    fn checked_div(self, other: Self) -> Option<Self>;
+    fn checked_rem(self, other: Self) -> Option<Self>;
+
+    fn is_zero(self) -> bool {
+        self == Self::zero()
+    }
+
+    fn is_negative(self) -> bool {
+        self < Self::zero()
+    }
+
+    /// Absolute value, `None` when it does not fit (e.g. `i32::MIN`)
+    fn checked_abs(self) -> Option<Self> {
+        if self.is_negative() {
+            Self::zero().checked_sub(self)
+        } else {
+            Some(self)
+        }
+    }
+
+    /// Euclidean remainder, always in `0..modulus.abs()`
+    fn checked_rem_euclid(self, modulus: Self) -> Option<Self> {
+        let modulus = modulus.checked_abs()?;
+        let rem = self.checked_rem(modulus)?;
+        if rem.is_negative() {
+            rem.checked_add(modulus)
+        } else {
+            Some(rem)
+        }
+    }
+}
+
+macro_rules! impl_integer {
+    ($($t:ty),*) => {
+        $(
+            impl Integer for $t {
+                fn zero() -> Self {
+                    0
+                }
+                fn one() -> Self {
+                    1
+                }
+                fn checked_add(self, other: Self) -> Option<Self> {
+                    <$t>::checked_add(self, other)
+                }
+                fn checked_sub(self, other: Self) -> Option<Self> {
+                    <$t>::checked_sub(self, other)
+                }
+                fn checked_mul(self, other: Self) -> Option<Self> {
+                    <$t>::checked_mul(self, other)
+                }
+                fn checked_div(self, other: Self) -> Option<Self> {
+                    <$t>::checked_div(self, other)
+                }
+                fn checked_rem(self, other: Self) -> Option<Self> {
+                    <$t>::checked_rem(self, other)
+                }
+            }
+        )*
+    };
+}
+
+impl_integer!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+/// Greatest common divisor, always non-negative
+///
+/// `gcd(0, 0)` is `0`. Returns `None` only when the result does not fit,
+/// which can only happen for signed `MIN` values.
+pub fn gcd<T: Integer>(a: T, b: T) -> Option<T> {
+    let (mut a, mut b) = (a.checked_abs()?, b.checked_abs()?);
+    while !b.is_zero() {
+        let rem = a.checked_rem(b)?;
+        a = b;
+        b = rem;
+    }
+    Some(a)
+}
+
+/// Least common multiple, `None` on overflow
+///
+/// `lcm(0, x)` is `0`.
+pub fn lcm<T: Integer>(a: T, b: T) -> Option<T> {
+    if a.is_zero() || b.is_zero() {
+        return Some(T::zero());
+    }
+    a.checked_div(gcd(a, b)?)?.checked_mul(b)?.checked_abs()
+}
+
+/// GCD of every value, `None` when empty
+pub fn gcd_all<T: Integer>(nums: impl IntoIterator<Item = T>) -> Option<T> {
+    let mut nums = nums.into_iter();
+    let first = nums.next()?.checked_abs()?;
+    nums.try_fold(first, |acc, num| gcd(acc, num))
+}
+
+/// LCM of every value, `None` when empty or on overflow
+pub fn lcm_all<T: Integer>(nums: impl IntoIterator<Item = T>) -> Option<T> {
+    let mut nums = nums.into_iter();
+    let first = nums.next()?.checked_abs()?;
+    nums.try_fold(first, |acc, num| lcm(acc, num))
+}
+
+/// Extended Euclid: returns `(g, x, y)` with `a * x + b * y == g == gcd(a, b)`
+///
+/// Only meaningful for signed types since the coefficients can be negative.
+pub fn extended_gcd<T: Integer>(a: T, b: T) -> Option<(T, T, T)> {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_s, mut s) = (T::one(), T::zero());
+    let (mut old_t, mut t) = (T::zero(), T::one());
+
+    while !r.is_zero() {
+        let quotient = old_r.checked_div(r)?;
+        (old_r, r) = (r, old_r.checked_sub(quotient.checked_mul(r)?)?);
+        (old_s, s) = (s, old_s.checked_sub(quotient.checked_mul(s)?)?);
+        (old_t, t) = (t, old_t.checked_sub(quotient.checked_mul(t)?)?);
+    }
+
+    // normalize so the gcd comes out non-negative
+    if old_r.is_negative() {
+        let negate = |x: T| T::zero().checked_sub(x);
+        return Some((negate(old_r)?, negate(old_s)?, negate(old_t)?));
+    }
+    Some((old_r, old_s, old_t))
+}
+
+/// `(a + b) % modulus` for `a, b` already reduced into `0..modulus`, without overflow
+pub fn add_mod<T: Integer>(a: T, b: T, modulus: T) -> Option<T> {
+    let gap = modulus.checked_sub(b)?;
+    if a >= gap {
+        a.checked_sub(gap)
+    } else {
+        a.checked_add(b)
+    }
+}
+
+/// `(a * b) % modulus` that never overflows as long as `modulus` itself fits
+///
+/// Falls back to double-and-add when the direct product does not fit.
+pub fn mul_mod<T: Integer>(a: T, b: T, modulus: T) -> Option<T> {
+    let a = a.checked_rem_euclid(modulus)?;
+    let mut b = b.checked_rem_euclid(modulus)?;
+    if let Some(product) = a.checked_mul(b) {
+        return product.checked_rem(modulus.checked_abs()?);
+    }
+
+    let modulus = modulus.checked_abs()?;
+    let two = T::one().checked_add(T::one())?;
+    let mut result = T::zero();
+    let mut addend = a;
+    while !b.is_zero() {
+        if !b.checked_rem(two)?.is_zero() {
+            result = add_mod(result, addend, modulus)?;
+        }
+        addend = add_mod(addend, addend, modulus)?;
+        b = b.checked_div(two)?;
+    }
+    Some(result)
+}
+
+/// `base.pow(exp) % modulus` by square-and-multiply, `None` for a negative exponent
+pub fn mod_pow<T: Integer>(base: T, exp: T, modulus: T) -> Option<T> {
+    if exp.is_negative() || modulus.is_zero() {
+        return None;
+    }
+    let two = T::one().checked_add(T::one())?;
+    let mut result = T::one().checked_rem_euclid(modulus)?;
+    let mut base = base.checked_rem_euclid(modulus)?;
+    let mut exp = exp;
+    while !exp.is_zero() {
+        if !exp.checked_rem(two)?.is_zero() {
+            result = mul_mod(result, base, modulus)?;
+        }
+        base = mul_mod(base, base, modulus)?;
+        exp = exp.checked_div(two)?;
+    }
+    Some(result)
+}
+
+/// Multiplicative inverse of `a` modulo `modulus`, `None` when they share a factor
+///
+/// Runs extended Euclid with the coefficient kept reduced modulo `modulus`,
+/// so it also works for unsigned types.
+pub fn mod_inverse<T: Integer>(a: T, modulus: T) -> Option<T> {
+    let modulus = modulus.checked_abs()?;
+    if modulus == T::one() {
+        return Some(T::zero());
+    }
+    let (mut old_r, mut r) = (a.checked_rem_euclid(modulus)?, modulus);
+    let (mut old_s, mut s) = (T::one(), T::zero());
+
+    while !r.is_zero() {
+        let quotient = old_r.checked_div(r)?;
+        (old_r, r) = (r, old_r.checked_sub(quotient.checked_mul(r)?)?);
+        let step = mul_mod(quotient, s, modulus)?;
+        let next_s = add_mod(
+            old_s,
+            modulus.checked_sub(step)?.checked_rem(modulus)?,
+            modulus,
+        )?;
+        (old_s, s) = (s, next_s);
+    }
+
+    if old_r != T::one() {
+        return None;
+    }
+    Some(old_s)
+}
+
+/// Chinese Remainder Theorem over `(residue, modulus)` pairs
+///
+/// Returns `(x, m)` where `x` is the smallest non-negative solution and `m`
+/// is the lcm of the moduli, so every solution is `x + k * m`. Moduli do not
+/// need to be pairwise coprime; `None` means the congruences are
+/// inconsistent or an intermediate value overflowed.
+pub fn crt<T: Integer>(congruences: impl IntoIterator<Item = (T, T)>) -> Option<(T, T)> {
+    congruences
+        .into_iter()
+        .try_fold((T::zero(), T::one()), |(x, m), (residue, modulus)| {
+            let modulus = modulus.checked_abs()?;
+            let residue = residue.checked_rem_euclid(modulus)?;
+
+            // x + m * k == residue (mod modulus)  =>  m * k == diff (mod modulus)
+            let g = gcd(m, modulus)?;
+            let x_reduced = x.checked_rem(modulus)?;
+            let diff = add_mod(
+                residue,
+                modulus.checked_sub(x_reduced)?.checked_rem(modulus)?,
+                modulus,
+            )?;
+            if !diff.checked_rem(g)?.is_zero() {
+                return None;
+            }
+
+            let reduced_modulus = modulus.checked_div(g)?;
+            let inverse = if reduced_modulus == T::one() {
+                T::zero()
+            } else {
+                mod_inverse(m.checked_div(g)?, reduced_modulus)?
+            };
+            let k = mul_mod(diff.checked_div(g)?, inverse, reduced_modulus)?;
+
+            let combined = m.checked_mul(reduced_modulus)?;
+            let next_x = add_mod(x, mul_mod(m, k, combined)?, combined)?;
+            Some((next_x, combined))
+        })
+}
+
+/// Floor of the square root, `None` for negative input
+pub fn isqrt<T: Integer>(n: T) -> Option<T> {
+    if n.is_negative() {
+        return None;
+    }
+    if n < T::one().checked_add(T::one())? {
+        return Some(n);
+    }
+
+    // Newton's method from an overestimate; converges monotonically downwards
+    let two = T::one().checked_add(T::one())?;
+    let mut x = n;
+    let mut next = n.checked_div(two)?;
+    while next < x {
+        x = next;
+        next = x.checked_add(n.checked_div(x)?)?.checked_div(two)?;
+    }
+    Some(x)
+}
+
+/// Every positive divisor of `n` in ascending order
+///
+/// Uses the absolute value for negative input; `0` has no divisors listed.
+pub fn divisors<T: Integer>(n: T) -> Option<Vec<T>> {
+    let n = n.checked_abs()?;
+    let mut small = Vec::new();
+    let mut large = Vec::new();
+    let mut i = T::one();
+    while i <= n.checked_div(i).unwrap_or(T::zero()) {
+        if n.checked_rem(i)?.is_zero() {
+            let pair = n.checked_div(i)?;
+            small.push(i);
+            if pair != i {
+                large.push(pair);
+            }
+        }
+        i = i.checked_add(T::one())?;
+    }
+    small.extend(large.into_iter().rev());
+    Some(small)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gcd_and_lcm() {
+        assert_eq!(gcd(12_u32, 18), Some(6));
+        assert_eq!(gcd(-12_i64, 18), Some(6));
+        assert_eq!(gcd(0_i32, 0), Some(0));
+        assert_eq!(lcm(4_usize, 6), Some(12));
+        assert_eq!(lcm_all([2_u64, 3, 4, 5]), Some(60));
+        assert_eq!(lcm_all(Vec::<u64>::new()), None);
+        assert_eq!(lcm(u8::MAX, 254), None);
+    }
+
+    #[test]
+    fn extended_gcd_coefficients() {
+        let (g, x, y) = extended_gcd(240_i64, 46).unwrap();
+        assert_eq!(g, 2);
+        assert_eq!(240 * x + 46 * y, 2);
+    }
+
+    #[test]
+    fn modular_arithmetic() {
+        assert_eq!(mod_inverse(3_i32, 11), Some(4));
+        assert_eq!(mod_inverse(6_i32, 9), None);
+        assert_eq!(mod_inverse(7_u64, 40), Some(23));
+        assert_eq!(mod_pow(2_u64, 10, 1_000), Some(24));
+        // product overflows u64 but the result does not
+        assert_eq!(mul_mod(u64::MAX - 1, u64::MAX - 1, u64::MAX), Some(1));
+    }
+
+    #[test]
+    fn chinese_remainder() {
+        assert_eq!(crt([(2_i64, 3), (3, 5), (2, 7)]), Some((23, 105)));
+        // non-coprime moduli
+        assert_eq!(crt([(2_i64, 4), (4, 6)]), Some((10, 12)));
+        assert_eq!(crt([(1_i64, 4), (2, 6)]), None);
+        assert_eq!(crt([(0_u64, 2), (1, 3)]), Some((4, 6)));
+    }
+
+    #[test]
+    fn roots_and_divisors() {
+        assert_eq!(isqrt(99_u32), Some(9));
+        assert_eq!(isqrt(u64::MAX), Some(4_294_967_295));
+        assert_eq!(divisors(36_i32), Some(vec![1, 2, 3, 4, 6, 9, 12, 18, 36]));
+    }
+}
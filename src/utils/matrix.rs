@@ -0,0 +1,122 @@
+use crate::utils::math::{add_mod, mul_mod, Integer};
+
+/// Square matrix of integers, for linear recurrences
+///
+/// Every operation takes an optional modulus. Without one the arithmetic
+/// is exact and overflow gives `None`; with one all entries are reduced
+/// so the only limit is the modulus fitting in `T`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Matrix<T> {
+    size: usize,
+    cells: Vec<T>,
+}
+
+impl<T: Integer> Matrix<T> {
+    pub fn zero(size: usize) -> Self {
+        Self {
+            size,
+            cells: vec![T::zero(); size * size],
+        }
+    }
+
+    pub fn identity(size: usize) -> Self {
+        let mut matrix = Self::zero(size);
+        for i in 0..size {
+            matrix.set(i, i, T::one());
+        }
+        matrix
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> T {
+        self.cells[row * self.size + col]
+    }
+
+    pub fn set(&mut self, row: usize, col: usize, value: T) {
+        self.cells[row * self.size + col] = value;
+    }
+
+    fn add(a: T, b: T, modulus: Option<T>) -> Option<T> {
+        match modulus {
+            Some(modulus) => add_mod(a, b, modulus),
+            None => a.checked_add(b),
+        }
+    }
+
+    fn mul(a: T, b: T, modulus: Option<T>) -> Option<T> {
+        match modulus {
+            Some(modulus) => mul_mod(a, b, modulus),
+            None => a.checked_mul(b),
+        }
+    }
+
+    pub fn checked_mul(&self, other: &Self, modulus: Option<T>) -> Option<Self> {
+        let mut product = Self::zero(self.size);
+        for row in 0..self.size {
+            for col in 0..self.size {
+                let mut sum = T::zero();
+                for k in 0..self.size {
+                    let term = Self::mul(self.get(row, k), other.get(k, col), modulus)?;
+                    sum = Self::add(sum, term, modulus)?;
+                }
+                product.set(row, col, sum);
+            }
+        }
+        Some(product)
+    }
+
+    /// `self` raised to `exp` by repeated squaring, `O(size³ · log exp)`
+    pub fn pow(&self, mut exp: u64, modulus: Option<T>) -> Option<Self> {
+        let mut result = Self::identity(self.size);
+        let mut base = self.clone();
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = result.checked_mul(&base, modulus)?;
+            }
+            exp >>= 1;
+            // skipping the last squaring keeps exact powers from overflowing early
+            if exp > 0 {
+                base = base.checked_mul(&base, modulus)?;
+            }
+        }
+        Some(result)
+    }
+
+    /// `self * vector` for a column vector
+    pub fn apply(&self, vector: &[T], modulus: Option<T>) -> Option<Vec<T>> {
+        (0..self.size)
+            .map(|row| {
+                (0..self.size).try_fold(T::zero(), |sum, col| {
+                    let term = Self::mul(self.get(row, col), vector[col], modulus)?;
+                    Self::add(sum, term, modulus)
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn raises_fibonacci_matrix() {
+        let mut fibonacci: Matrix<u64> = Matrix::zero(2);
+        fibonacci.set(0, 0, 1);
+        fibonacci.set(0, 1, 1);
+        fibonacci.set(1, 0, 1);
+        assert_eq!(
+            fibonacci.pow(90, None).unwrap().get(0, 1),
+            2880067194370816120
+        );
+        assert_eq!(fibonacci.pow(100, None), None);
+        assert_eq!(
+            fibonacci.pow(100, Some(1_000_000_007)).unwrap().get(0, 1),
+            687995182
+        );
+        assert_eq!(fibonacci.apply(&[2, 1], None).unwrap(), vec![3, 2]);
+    }
+}
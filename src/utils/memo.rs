@@ -0,0 +1,189 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+
+/// Hit/miss counters for a [`Memo`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoStats {
+    pub hits: usize,
+    pub misses: usize,
+    pub entries: usize,
+}
+
+impl MemoStats {
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            0.0
+        } else {
+            self.hits as f64 / lookups as f64
+        }
+    }
+}
+
+impl fmt::Display for MemoStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "hits={} misses={} entries={} hit_rate={:.2}%",
+            self.hits,
+            self.misses,
+            self.entries,
+            self.hit_rate() * 100.0
+        )
+    }
+}
+
+/// Cache for recursive solvers that counts how often it actually helped
+///
+/// The closure passed to [`Memo::get_or_compute`] receives the memo back so
+/// it can recurse through the same cache:
+///
+/// ```ignore
+/// fn count(memo: &mut Memo<u64, u64>, n: u64) -> u64 {
+///     memo.get_or_compute(n, |memo| if n < 2 { n } else { count(memo, n - 1) + count(memo, n - 2) })
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Memo<K, V> {
+    cache: HashMap<K, V>,
+    hits: usize,
+    misses: usize,
+}
+
+impl<K: Eq + Hash, V: Clone> Memo<K, V> {
+    pub fn new() -> Self {
+        Self {
+            cache: HashMap::new(),
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Cached value for `key`, recording a hit or a miss
+    pub fn get(&mut self, key: &K) -> Option<V> {
+        match self.cache.get(key) {
+            Some(value) => {
+                self.hits += 1;
+                Some(value.clone())
+            }
+            None => {
+                self.misses += 1;
+                None
+            }
+        }
+    }
+
+    pub fn insert(&mut self, key: K, value: V) {
+        self.cache.insert(key, value);
+    }
+
+    /// Cached value for `key`, or runs `compute` and stores its result
+    pub fn get_or_compute<F>(&mut self, key: K, compute: F) -> V
+    where
+        F: FnOnce(&mut Self) -> V,
+    {
+        if let Some(value) = self.get(&key) {
+            return value;
+        }
+        let value = compute(self);
+        self.cache.insert(key, value.clone());
+        value
+    }
+
+    /// Drops every entry and resets the counters
+    pub fn clear(&mut self) {
+        self.cache.clear();
+        self.hits = 0;
+        self.misses = 0;
+    }
+
+    pub fn stats(&self) -> MemoStats {
+        MemoStats {
+            hits: self.hits,
+            misses: self.misses,
+            entries: self.cache.len(),
+        }
+    }
+}
+
+impl<K: Eq + Hash, V: Clone> Default for Memo<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A recursive function bundled with its own [`Memo`]
+///
+/// The function gets the wrapper back as its first argument and recurses by
+/// calling [`Memoized::call`], which is about as close to a `#[memoize]`
+/// attribute as we get without a proc macro:
+///
+/// ```ignore
+/// let mut fib = Memoized::new(|fib, n: u64| if n < 2 { n } else { fib.call(n - 1) + fib.call(n - 2) });
+/// fib.call(90);
+/// ```
+pub struct Memoized<K, V> {
+    memo: Memo<K, V>,
+    function: fn(&mut Memoized<K, V>, K) -> V,
+}
+
+impl<K: Eq + Hash + Clone, V: Clone> Memoized<K, V> {
+    pub fn new(function: fn(&mut Memoized<K, V>, K) -> V) -> Self {
+        Self {
+            memo: Memo::new(),
+            function,
+        }
+    }
+
+    pub fn call(&mut self, key: K) -> V {
+        if let Some(value) = self.memo.get(&key) {
+            return value;
+        }
+        let value = (self.function)(self, key.clone());
+        self.memo.insert(key, value.clone());
+        value
+    }
+
+    pub fn stats(&self) -> MemoStats {
+        self.memo.stats()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fib(memo: &mut Memo<u64, u64>, n: u64) -> u64 {
+        memo.get_or_compute(n, |memo| {
+            if n < 2 {
+                n
+            } else {
+                fib(memo, n - 1) + fib(memo, n - 2)
+            }
+        })
+    }
+
+    #[test]
+    fn memo_counts_hits_and_misses() {
+        let mut memo = Memo::new();
+        assert_eq!(fib(&mut memo, 50), 12_586_269_025);
+        let stats = memo.stats();
+        assert_eq!(stats.entries, 51);
+        assert_eq!(stats.misses, 51);
+        assert_eq!(stats.hits, 48);
+    }
+
+    #[test]
+    fn memoized_function_recurses_through_cache() {
+        let mut fib = Memoized::new(|fib, n: u64| {
+            if n < 2 {
+                n
+            } else {
+                fib.call(n - 1) + fib.call(n - 2)
+            }
+        });
+        assert_eq!(fib.call(90), 2_880_067_194_370_816_120);
+        assert_eq!(fib.stats().entries, 91);
+    }
+}
@@ -0,0 +1,12 @@
+pub mod answer;
+pub mod asm;
+pub mod bigint;
+pub mod cycle;
+pub mod error;
+pub mod expr;
+pub mod geometry;
+pub mod math;
+pub mod matrix;
+pub mod memo;
+pub mod ocr;
+pub mod parse;
@@ -0,0 +1,254 @@
+use crate::utils::error::{AocError, AocResult};
+use std::collections::HashSet;
+
+/// Block letters from the 6-pixel-tall displays (2016 day 8, 2019 days 8 and 11, ...)
+///
+/// Stored trimmed to their lit columns since `I` and `Y` are not 4 wide like the rest.
+const SMALL_FONT: [(char, &str); 18] = [
+    ('A', ".##.\n#..#\n#..#\n####\n#..#\n#..#"),
+    ('B', "###.\n#..#\n###.\n#..#\n#..#\n###."),
+    ('C', ".##.\n#..#\n#...\n#...\n#..#\n.##."),
+    ('E', "####\n#...\n###.\n#...\n#...\n####"),
+    ('F', "####\n#...\n###.\n#...\n#...\n#..."),
+    ('G', ".##.\n#..#\n#...\n#.##\n#..#\n.###"),
+    ('H', "#..#\n#..#\n####\n#..#\n#..#\n#..#"),
+    ('I', "###\n.#.\n.#.\n.#.\n.#.\n###"),
+    ('J', "..##\n...#\n...#\n...#\n#..#\n.##."),
+    ('K', "#..#\n#.#.\n##..\n#.#.\n#.#.\n#..#"),
+    ('L', "#...\n#...\n#...\n#...\n#...\n####"),
+    ('O', ".##.\n#..#\n#..#\n#..#\n#..#\n.##."),
+    ('P', "###.\n#..#\n#..#\n###.\n#...\n#..."),
+    ('R', "###.\n#..#\n#..#\n###.\n#.#.\n#..#"),
+    ('S', ".###\n#...\n#...\n.##.\n...#\n###."),
+    ('U', "#..#\n#..#\n#..#\n#..#\n#..#\n.##."),
+    ('Y', "#...#\n#...#\n.#.#.\n..#..\n..#..\n..#.."),
+    ('Z', "####\n...#\n..#.\n.#..\n#...\n####"),
+];
+
+/// Block letters from the 10-pixel-tall message in the stars (2018 day 10)
+const LARGE_FONT: [(char, &str); 15] = [
+    (
+        'A',
+        "..##..\n.#..#.\n#....#\n#....#\n#....#\n######\n#....#\n#....#\n#....#\n#....#",
+    ),
+    (
+        'B',
+        "#####.\n#....#\n#....#\n#....#\n#####.\n#....#\n#....#\n#....#\n#....#\n#####.",
+    ),
+    (
+        'C',
+        ".####.\n#....#\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#....#\n.####.",
+    ),
+    (
+        'E',
+        "######\n#.....\n#.....\n#.....\n#####.\n#.....\n#.....\n#.....\n#.....\n######",
+    ),
+    (
+        'F',
+        "######\n#.....\n#.....\n#.....\n#####.\n#.....\n#.....\n#.....\n#.....\n#.....",
+    ),
+    (
+        'G',
+        ".####.\n#....#\n#.....\n#.....\n#.....\n#..###\n#....#\n#....#\n#...##\n.###.#",
+    ),
+    (
+        'H',
+        "#....#\n#....#\n#....#\n#....#\n######\n#....#\n#....#\n#....#\n#....#\n#....#",
+    ),
+    (
+        'J',
+        "...###\n....#.\n....#.\n....#.\n....#.\n....#.\n....#.\n#...#.\n#...#.\n.###..",
+    ),
+    (
+        'K',
+        "#....#\n#...#.\n#..#..\n#.#...\n##....\n##....\n#.#...\n#..#..\n#...#.\n#....#",
+    ),
+    (
+        'L',
+        "#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n######",
+    ),
+    (
+        'N',
+        "#....#\n##...#\n##...#\n#.#..#\n#.#..#\n#..#.#\n#..#.#\n#...##\n#...##\n#....#",
+    ),
+    (
+        'P',
+        "#####.\n#....#\n#....#\n#....#\n#####.\n#.....\n#.....\n#.....\n#.....\n#.....",
+    ),
+    (
+        'R',
+        "#####.\n#....#\n#....#\n#....#\n#####.\n#..#..\n#...#.\n#...#.\n#....#\n#....#",
+    ),
+    (
+        'X',
+        "#....#\n#....#\n.#..#.\n.#..#.\n..##..\n..##..\n.#..#.\n.#..#.\n#....#\n#....#",
+    ),
+    (
+        'Z',
+        "######\n.....#\n.....#\n....#.\n...#..\n..#...\n.#....\n#.....\n#.....\n######",
+    ),
+];
+
+/// Reads the letters spelled out by a set of lit `(col, row)` pixels
+///
+/// Letters are matched left to right against the font, so the spacing
+/// between them does not matter. The font is picked from the height of the
+/// lit area.
+pub fn recognize(pixels: &HashSet<(usize, usize)>) -> AocResult<String> {
+    let (Some(min_col), Some(max_col)) = (
+        pixels.iter().map(|(col, _)| *col).min(),
+        pixels.iter().map(|(col, _)| *col).max(),
+    ) else {
+        return Ok(String::new());
+    };
+    let min_row = pixels.iter().map(|(_, row)| *row).min().unwrap_or(0);
+    let max_row = pixels.iter().map(|(_, row)| *row).max().unwrap_or(0);
+    let height = max_row - min_row + 1;
+
+    let font: &[(char, &str)] = match height {
+        6 => &SMALL_FONT,
+        10 => &LARGE_FONT,
+        _ => {
+            return Err(AocError::parse(
+                &render(pixels, min_col..=max_col, min_row..=max_row),
+                format!("no font is {height} pixels tall"),
+            ))
+        }
+    };
+
+    let lit_column = |col: usize| (min_row..=max_row).any(|row| pixels.contains(&(col, row)));
+    let width_of = |shape: &str| shape.find('\n').unwrap_or(shape.len());
+
+    let mut text = String::new();
+    let mut unrecognized = Vec::new();
+    let mut col = min_col;
+    while col <= max_col {
+        if !lit_column(col) {
+            col += 1;
+            continue;
+        }
+
+        // letters normally end on a dark column, but a wide one like `Y` can
+        // touch its neighbour, so fall back to any template that fits
+        let fits = |shape: &str| {
+            render(pixels, col..=col + width_of(shape) - 1, min_row..=max_row) == shape
+        };
+        let matched = font
+            .iter()
+            .filter(|(_, shape)| fits(shape))
+            .max_by_key(|(_, shape)| !lit_column(col + width_of(shape)));
+
+        match matched {
+            Some((letter, shape)) => {
+                text.push(*letter);
+                col += width_of(shape);
+            }
+            None => {
+                let start = col;
+                while col <= max_col && lit_column(col) {
+                    col += 1;
+                }
+                text.push('?');
+                unrecognized.push((
+                    text.len() - 1,
+                    render(pixels, start..=col - 1, min_row..=max_row),
+                ));
+            }
+        }
+    }
+
+    if unrecognized.is_empty() {
+        Ok(text)
+    } else {
+        Err(AocError::UnrecognizedGlyphs { text, unrecognized })
+    }
+}
+
+/// Same as [`recognize`] for a row-major grid where `true` is lit
+pub fn recognize_grid(grid: &[Vec<bool>]) -> AocResult<String> {
+    let pixels = grid
+        .iter()
+        .enumerate()
+        .flat_map(|(row, line)| {
+            line.iter()
+                .enumerate()
+                .filter(|(_, lit)| **lit)
+                .map(move |(col, _)| (col, row))
+        })
+        .collect();
+    recognize(&pixels)
+}
+
+fn render(
+    pixels: &HashSet<(usize, usize)>,
+    cols: std::ops::RangeInclusive<usize>,
+    rows: std::ops::RangeInclusive<usize>,
+) -> String {
+    rows.map(|row| {
+        cols.clone()
+            .map(|col| {
+                if pixels.contains(&(col, row)) {
+                    '#'
+                } else {
+                    '.'
+                }
+            })
+            .collect::<String>()
+    })
+    .collect::<Vec<String>>()
+    .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(text: &str) -> Vec<Vec<bool>> {
+        text.lines()
+            .map(|line| line.chars().map(|c| c == '#').collect())
+            .collect()
+    }
+
+    #[test]
+    fn reads_small_font() {
+        let screen = grid(
+            "\
+#..#.####.#....#.....##..
+#..#.#....#....#....#..#.
+####.###..#....#....#..#.
+#..#.#....#....#....#..#.
+#..#.#....#....#....#..#.
+#..#.####.####.####..##..",
+        );
+        assert_eq!(recognize_grid(&screen).unwrap(), "HELLO");
+    }
+
+    #[test]
+    fn reads_every_glyph_in_both_fonts() {
+        for font in [&SMALL_FONT[..], &LARGE_FONT[..]] {
+            // every letter side by side with a dark column after each
+            let height = font[0].1.lines().count();
+            let rows: Vec<String> = (0..height)
+                .map(|row| {
+                    font.iter()
+                        .map(|(_, shape)| format!("{}.", shape.lines().nth(row).unwrap()))
+                        .collect()
+                })
+                .collect();
+            let expected: String = font.iter().map(|(letter, _)| *letter).collect();
+            assert_eq!(recognize_grid(&grid(&rows.join("\n"))).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn lists_unrecognized_glyphs() {
+        let screen = grid(".##..#\n#..#.#\n#..#.#\n####.#\n#..#.#\n#..#.#");
+        match recognize_grid(&screen) {
+            Err(AocError::UnrecognizedGlyphs { text, unrecognized }) => {
+                assert_eq!(text, "A?");
+                assert_eq!(unrecognized, vec![(1, "#\n#\n#\n#\n#\n#".to_string())]);
+            }
+            other => panic!("expected unrecognized glyphs, got {other:?}"),
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use crate::utils::error::{AocError, AocResult};
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::str::FromStr;
+
+lazy_static! {
+    static ref SIGNED_PATTERN: Regex = Regex::new(r"-?\d+").unwrap();
+    static ref UNSIGNED_PATTERN: Regex = Regex::new(r"\d+").unwrap();
+    static ref BLANK_LINE_PATTERN: Regex = Regex::new(r"\r?\n[ \t]*\r?\n").unwrap();
+}
+
+fn parse_number<T: FromStr>(text: &str) -> AocResult<T> {
+    text.parse()
+        .map_err(|_| AocError::parse(text, format!("{text:?} does not fit the target type")))
+}
+
+/// Every integer in `text`, with a leading `-` treated as a sign
+///
+/// `"#1 @ -3,4: 5x-6"` gives `[1, -3, 4, 5, -6]`.
+pub fn signed_ints<T: FromStr>(text: &str) -> AocResult<Vec<T>> {
+    SIGNED_PATTERN
+        .find_iter(text)
+        .map(|number| parse_number(number.as_str()))
+        .collect()
+}
+
+/// Every run of digits in `text`, ignoring any `-` so ranges like `2-4` stay positive
+pub fn unsigned_ints<T: FromStr>(text: &str) -> AocResult<Vec<T>> {
+    UNSIGNED_PATTERN
+        .find_iter(text)
+        .map(|number| parse_number(number.as_str()))
+        .collect()
+}
+
+/// Splits `"key: values"` on the first `separator`, trimming both sides
+pub fn key_values<'a>(text: &'a str, separator: &str) -> AocResult<(&'a str, &'a str)> {
+    text.split_once(separator)
+        .map(|(key, values)| (key.trim(), values.trim()))
+        .ok_or_else(|| AocError::parse(text, format!("separator {separator:?} not found")))
+}
+
+/// Blocks of lines separated by blank lines, each without trailing newline
+pub fn paragraphs(text: &str) -> Vec<&str> {
+    BLANK_LINE_PATTERN
+        .split(text)
+        .map(|paragraph| paragraph.trim_matches(['\r', '\n']))
+        .filter(|paragraph| !paragraph.is_empty())
+        .collect()
+}
+
+/// Runs `parse` on every line, tagging any error with its 1-based line number
+pub fn parse_lines<T, F>(text: &str, mut parse: F) -> AocResult<Vec<T>>
+where
+    F: FnMut(&str) -> AocResult<T>,
+{
+    text.lines()
+        .enumerate()
+        .map(|(i, line)| parse(line).map_err(|error| error.on_line(i + 1, line)))
+        .collect()
+}
+
+/// Pieces of `text` that line up with each `{}` in `pattern`
+///
+/// Literal parts of the pattern must match exactly; each placeholder takes
+/// everything up to the next literal (or the rest of the line for a
+/// trailing `{}`).
+pub fn template_fields<'a>(pattern: &str, text: &'a str) -> AocResult<Vec<&'a str>> {
+    let mut literals = pattern.split("{}");
+    let mut rest = text
+        .strip_prefix(literals.next().unwrap_or_default())
+        .ok_or_else(|| AocError::parse(text, format!("does not match {pattern:?}")))?;
+
+    let mut fields = Vec::new();
+    for literal in literals {
+        if literal.is_empty() {
+            fields.push(rest);
+            rest = "";
+            continue;
+        }
+        let (field, remainder) = rest.split_once(literal).ok_or_else(|| {
+            AocError::parse(
+                text,
+                format!("expected {literal:?} while matching {pattern:?}"),
+            )
+        })?;
+        fields.push(field);
+        rest = remainder;
+    }
+
+    if !rest.is_empty() {
+        return Err(AocError::parse(
+            text,
+            format!("unexpected trailing {rest:?} after matching {pattern:?}"),
+        ));
+    }
+    Ok(fields)
+}
+
+/// Typed tuples built from the fields of [`template_fields`]
+pub trait FromFields: Sized {
+    fn from_fields(fields: &[&str]) -> AocResult<Self>;
+}
+
+macro_rules! impl_from_fields {
+    ($count:literal; $($t:ident $i:tt),+) => {
+        impl<$($t: FromStr),+> FromFields for ($($t,)+) {
+            fn from_fields(fields: &[&str]) -> AocResult<Self> {
+                if fields.len() != $count {
+                    return Err(AocError::parse(
+                        &fields.join(" "),
+                        format!("expected {} fields, found {}", $count, fields.len()),
+                    ));
+                }
+                Ok(($(
+                    fields[$i].trim().parse::<$t>().map_err(|_| {
+                        AocError::parse(fields[$i], format!("could not parse field {}", $i + 1))
+                    })?,
+                )+))
+            }
+        }
+    };
+}
+
+impl_from_fields!(1; A 0);
+impl_from_fields!(2; A 0, B 1);
+impl_from_fields!(3; A 0, B 1, C 2);
+impl_from_fields!(4; A 0, B 1, C 2, D 3);
+impl_from_fields!(5; A 0, B 1, C 2, D 3, E 4);
+impl_from_fields!(6; A 0, B 1, C 2, D 3, E 4, F 5);
+
+/// Matches `text` against a `{}` pattern and parses each field into a tuple
+///
+/// ```ignore
+/// let (quantity, from, to): (usize, usize, usize) = scan("move {} from {} to {}", line)?;
+/// ```
+pub fn scan<T: FromFields>(pattern: &str, text: &str) -> AocResult<T> {
+    T::from_fields(&template_fields(pattern, text)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_integers() {
+        assert_eq!(
+            signed_ints::<i32>("x=-3, y=14..-2").unwrap(),
+            vec![-3, 14, -2]
+        );
+        assert_eq!(unsigned_ints::<u8>("2-4,6-8").unwrap(), vec![2, 4, 6, 8]);
+        assert!(signed_ints::<u8>("300").is_err());
+    }
+
+    #[test]
+    fn scans_templates() {
+        let (id, col, row, width, height): (u32, u32, u32, u32, u32) =
+            scan("#{} @ {},{}: {}x{}", "#1 @ 1,3: 4x4").unwrap();
+        assert_eq!((id, col, row, width, height), (1, 1, 3, 4, 4));
+        assert!(scan::<(u32, u32)>("{} -> {}", "1 => 2").is_err());
+    }
+
+    #[test]
+    fn reports_line_numbers() {
+        let error = parse_lines("1\n2\nthree", |line| scan::<(i32,)>("{}", line)).unwrap_err();
+        assert!(matches!(error, AocError::Parse { line: Some(3), .. }));
+    }
+
+    #[test]
+    fn splits_paragraphs_and_keys() {
+        assert_eq!(paragraphs("a\nb\n\nc\n"), vec!["a\nb", "c"]);
+        assert_eq!(paragraphs("a\r\n\r\nb"), vec!["a", "b"]);
+        assert_eq!(
+            key_values("Card 1: 41 48 | 83 86", ":").unwrap(),
+            ("Card 1", "41 48 | 83 86")
+        );
+    }
+}
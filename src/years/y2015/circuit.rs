@@ -0,0 +1,423 @@
+//! Wires and gates from 2015 day 7, evaluated with real 16-bit signals
+
+use crate::utils::error::{AocError, AocResult};
+use crate::utils::parse::parse_lines;
+use std::collections::HashMap;
+use std::fmt;
+
+/// Something a gate reads: another wire or a constant signal
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Input {
+    Wire(String),
+    Signal(u16),
+}
+
+impl Input {
+    fn parse(text: &str) -> Self {
+        match text.parse() {
+            Ok(signal) => Input::Signal(signal),
+            Err(_) => Input::Wire(text.to_string()),
+        }
+    }
+
+    fn wire(&self) -> Option<&str> {
+        match self {
+            Input::Wire(name) => Some(name),
+            Input::Signal(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for Input {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Input::Wire(name) => write!(f, "{name}"),
+            Input::Signal(signal) => write!(f, "{signal}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Gate {
+    Direct(Input),
+    Not(Input),
+    And(Input, Input),
+    Or(Input, Input),
+    LShift(Input, Input),
+    RShift(Input, Input),
+}
+
+impl Gate {
+    pub fn inputs(&self) -> Vec<&Input> {
+        match self {
+            Gate::Direct(input) | Gate::Not(input) => vec![input],
+            Gate::And(left, right)
+            | Gate::Or(left, right)
+            | Gate::LShift(left, right)
+            | Gate::RShift(left, right) => vec![left, right],
+        }
+    }
+
+    /// `AND`, `NOT`, ... or an empty string for a plain connection
+    pub fn name(&self) -> &'static str {
+        match self {
+            Gate::Direct(_) => "",
+            Gate::Not(_) => "NOT",
+            Gate::And(..) => "AND",
+            Gate::Or(..) => "OR",
+            Gate::LShift(..) => "LSHIFT",
+            Gate::RShift(..) => "RSHIFT",
+        }
+    }
+
+    /// Shifts by 16 or more clear every bit
+    fn apply(&self, values: &[u16]) -> u16 {
+        let shift = |value: u16, by: u16, shift: fn(u16, u32) -> Option<u16>| {
+            shift(value, u32::from(by)).unwrap_or(0)
+        };
+        match self {
+            Gate::Direct(_) => values[0],
+            Gate::Not(_) => !values[0],
+            Gate::And(..) => values[0] & values[1],
+            Gate::Or(..) => values[0] | values[1],
+            Gate::LShift(..) => shift(values[0], values[1], u16::checked_shl),
+            Gate::RShift(..) => shift(values[0], values[1], u16::checked_shr),
+        }
+    }
+}
+
+/// Same notation as the puzzle input, without the `-> wire` part
+impl fmt::Display for Gate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.inputs().as_slice() {
+            [input] if self.name().is_empty() => write!(f, "{input}"),
+            [input] => write!(f, "{} {input}", self.name()),
+            [left, right] => write!(f, "{left} {} {right}", self.name()),
+            _ => unreachable!("gates have one or two inputs"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CircuitError {
+    /// `wire` is read by a gate but nothing drives it
+    UndefinedWire { wire: String },
+    /// The wires feed back into each other, first one repeated at the end
+    Cycle { wires: Vec<String> },
+}
+
+impl fmt::Display for CircuitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CircuitError::UndefinedWire { wire } => write!(f, "wire {wire} is never driven"),
+            CircuitError::Cycle { wires } => write!(f, "cycle through {}", wires.join(" -> ")),
+        }
+    }
+}
+
+impl std::error::Error for CircuitError {}
+
+/// Gates indexed by the wire they drive
+///
+/// Signals are computed on demand in dependency order and cached until the
+/// wiring changes.
+#[derive(Debug, Clone, Default)]
+pub struct Circuit {
+    gates: HashMap<String, Gate>,
+    overrides: HashMap<String, u16>,
+    values: HashMap<String, u16>,
+}
+
+impl Circuit {
+    /// One `x AND y -> z` style line per gate, each wire driven only once
+    pub fn parse(text: &str) -> AocResult<Self> {
+        let mut circuit = Self::default();
+        parse_lines(text, |line| {
+            let (wire, gate) = parse_gate(line)?;
+            if circuit.gates.contains_key(&wire) {
+                return Err(AocError::parse(
+                    line,
+                    format!("wire {wire} is already driven"),
+                ));
+            }
+            circuit.gates.insert(wire, gate);
+            Ok(())
+        })?;
+        Ok(circuit)
+    }
+
+    pub fn gate(&self, wire: &str) -> Option<&Gate> {
+        self.gates.get(wire)
+    }
+
+    /// Every driven wire, in no particular order
+    pub fn wires(&self) -> impl Iterator<Item = &str> {
+        self.gates.keys().map(String::as_str)
+    }
+
+    pub fn overrides(&self) -> &HashMap<String, u16> {
+        &self.overrides
+    }
+
+    /// Forces `wire` to `signal`, ignoring whatever gate drives it
+    pub fn override_wire(&mut self, wire: &str, signal: u16) {
+        self.overrides.insert(wire.to_string(), signal);
+        self.values.clear();
+    }
+
+    pub fn clear_overrides(&mut self) {
+        self.overrides.clear();
+        self.values.clear();
+    }
+
+    /// Signal already computed for `wire`, if any
+    pub fn cached(&self, wire: &str) -> Option<u16> {
+        self.values.get(wire).copied()
+    }
+
+    /// Wires `wire` reads directly; none for an overridden wire
+    fn dependencies(&self, wire: &str) -> Result<Vec<String>, CircuitError> {
+        if self.overrides.contains_key(wire) {
+            return Ok(Vec::new());
+        }
+        let gate = self
+            .gates
+            .get(wire)
+            .ok_or_else(|| CircuitError::UndefinedWire {
+                wire: wire.to_string(),
+            })?;
+        Ok(gate
+            .inputs()
+            .into_iter()
+            .filter_map(|input| input.wire().map(str::to_string))
+            .collect())
+    }
+
+    /// `wire` and everything it depends on, dependencies first
+    pub fn topological_order(&self, wire: &str) -> Result<Vec<String>, CircuitError> {
+        #[derive(Clone, Copy, PartialEq)]
+        enum Mark {
+            Open,
+            Done,
+        }
+        let mut marks: HashMap<String, Mark> = HashMap::new();
+        let mut order = Vec::new();
+        // explicit stack of (wire, dependencies not yet visited) so deep
+        // chains don't overflow the call stack
+        let mut stack: Vec<(String, Vec<String>)> = Vec::new();
+
+        marks.insert(wire.to_string(), Mark::Open);
+        stack.push((wire.to_string(), self.dependencies(wire)?));
+        while let Some((current, pending)) = stack.last_mut() {
+            match pending.pop() {
+                Some(next) => match marks.get(&next) {
+                    Some(Mark::Done) => (),
+                    Some(Mark::Open) => {
+                        let start = stack
+                            .iter()
+                            .position(|(wire, _)| *wire == next)
+                            .expect("open wires are on the stack");
+                        let mut wires: Vec<String> = stack[start..]
+                            .iter()
+                            .map(|(wire, _)| wire.clone())
+                            .collect();
+                        wires.push(next);
+                        return Err(CircuitError::Cycle { wires });
+                    }
+                    None => {
+                        let dependencies = self.dependencies(&next)?;
+                        marks.insert(next.clone(), Mark::Open);
+                        stack.push((next, dependencies));
+                    }
+                },
+                None => {
+                    let current = current.clone();
+                    marks.insert(current.clone(), Mark::Done);
+                    order.push(current);
+                    stack.pop();
+                }
+            }
+        }
+        Ok(order)
+    }
+
+    pub fn signal(&mut self, wire: &str) -> Result<u16, CircuitError> {
+        if let Some(value) = self.cached(wire) {
+            return Ok(value);
+        }
+        for current in self.topological_order(wire)? {
+            if self.values.contains_key(&current) {
+                continue;
+            }
+            let value = match self.overrides.get(&current) {
+                Some(signal) => *signal,
+                None => {
+                    let gate = &self.gates[&current];
+                    let inputs: Vec<u16> = gate
+                        .inputs()
+                        .into_iter()
+                        .map(|input| match input {
+                            Input::Wire(name) => self.values[name],
+                            Input::Signal(signal) => *signal,
+                        })
+                        .collect();
+                    gate.apply(&inputs)
+                }
+            };
+            self.values.insert(current, value);
+        }
+        Ok(self.values[wire])
+    }
+
+    /// Evaluates every driven wire
+    pub fn evaluate(&mut self) -> Result<&HashMap<String, u16>, CircuitError> {
+        let wires: Vec<String> = self.gates.keys().cloned().collect();
+        for wire in wires {
+            self.signal(&wire)?;
+        }
+        Ok(&self.values)
+    }
+
+    /// Graphviz source for the whole circuit, or just the wires feeding `wire`
+    ///
+    /// Each node shows the wire, the gate driving it and its evaluated signal;
+    /// overridden wires are drawn dashed. Render with `dot -Tsvg`.
+    pub fn to_dot(&mut self, wire: Option<&str>) -> Result<String, CircuitError> {
+        let wires = match wire {
+            Some(wire) => {
+                self.signal(wire)?;
+                self.topological_order(wire)?
+            }
+            None => {
+                self.evaluate()?;
+                let mut wires: Vec<String> = self.gates.keys().cloned().collect();
+                wires.sort();
+                wires
+            }
+        };
+
+        let mut dot = String::from("digraph circuit {\n    rankdir=LR;\n    node [shape=box];\n");
+        for wire in &wires {
+            let value = self.values[wire];
+            match self.overrides.get(wire) {
+                Some(_) => dot.push_str(&format!(
+                    "    \"{wire}\" [label=\"{wire}\\noverride\\n{value}\", style=dashed];\n"
+                )),
+                None => dot.push_str(&format!(
+                    "    \"{wire}\" [label=\"{wire}\\n{}\\n{value}\"];\n",
+                    self.gates[wire]
+                )),
+            }
+        }
+        for wire in &wires {
+            for source in self.dependencies(wire)? {
+                dot.push_str(&format!("    \"{source}\" -> \"{wire}\";\n"));
+            }
+        }
+        dot.push_str("}\n");
+        Ok(dot)
+    }
+}
+
+const USAGE: &str = "usage: aoc circuit dot <file> [wire]";
+
+/// Entry point for `aoc circuit ...`, printing DOT to stdout
+pub fn run(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    match args {
+        [command, path, wire @ ..] if command == "dot" && wire.len() <= 1 => {
+            let text = std::fs::read_to_string(path)
+                .map_err(|error| format!("failed to read {path}: {error}"))?;
+            let mut circuit = Circuit::parse(&text)?;
+            print!("{}", circuit.to_dot(wire.first().map(String::as_str))?);
+            Ok(())
+        }
+        _ => Err(USAGE.into()),
+    }
+}
+
+fn parse_gate(line: &str) -> AocResult<(String, Gate)> {
+    let (expression, wire) = line
+        .split_once(" -> ")
+        .ok_or_else(|| AocError::parse(line, "expected `... -> wire`"))?;
+    let words: Vec<&str> = expression.split_whitespace().collect();
+    let gate = match words.as_slice() {
+        [input] => Gate::Direct(Input::parse(input)),
+        ["NOT", input] => Gate::Not(Input::parse(input)),
+        [left, operator, right] => {
+            let (left, right) = (Input::parse(left), Input::parse(right));
+            match *operator {
+                "AND" => Gate::And(left, right),
+                "OR" => Gate::Or(left, right),
+                "LSHIFT" => Gate::LShift(left, right),
+                "RSHIFT" => Gate::RShift(left, right),
+                _ => return Err(AocError::parse(line, format!("unknown gate {operator}"))),
+            }
+        }
+        _ => return Err(AocError::parse(line, "unrecognized gate")),
+    };
+    Ok((wire.trim().to_string(), gate))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "123 -> x
+456 -> y
+x AND y -> d
+x OR y -> e
+x LSHIFT 2 -> f
+y RSHIFT 2 -> g
+NOT x -> h
+NOT y -> i";
+
+    #[test]
+    fn evaluates_with_16_bit_signals() {
+        let mut circuit = Circuit::parse(EXAMPLE).unwrap();
+        let values = circuit.evaluate().unwrap();
+        assert_eq!(values["d"], 72);
+        assert_eq!(values["f"], 492);
+        assert_eq!(values["h"], 65412);
+        assert_eq!(values["i"], 65079);
+
+        circuit.override_wire("x", 0);
+        assert_eq!(circuit.signal("h").unwrap(), u16::MAX);
+    }
+
+    #[test]
+    fn exports_the_cone_of_a_wire() {
+        let mut circuit = Circuit::parse(EXAMPLE).unwrap();
+        circuit.override_wire("y", 1);
+        let dot = circuit.to_dot(Some("d")).unwrap();
+        assert!(dot.contains("\"d\" [label=\"d\\nx AND y\\n1\"];"));
+        assert!(dot.contains("\"y\" [label=\"y\\noverride\\n1\", style=dashed];"));
+        assert!(dot.contains("\"x\" -> \"d\";"));
+        assert!(!dot.contains("\"e\""));
+    }
+
+    #[test]
+    fn rejects_wires_with_two_drivers() {
+        assert_eq!(
+            Circuit::parse("1 -> a\n2 -> a").unwrap_err(),
+            AocError::parse("", "wire a is already driven").on_line(2, "2 -> a")
+        );
+    }
+
+    #[test]
+    fn reports_undefined_and_cyclic_wires() {
+        let mut circuit = Circuit::parse("q AND a -> b\nb -> c\nNOT c -> a").unwrap();
+        assert_eq!(
+            circuit.signal("c"),
+            Err(CircuitError::Cycle {
+                wires: ["c", "b", "a", "c"].map(String::from).to_vec()
+            })
+        );
+        circuit.override_wire("a", 1);
+        assert_eq!(
+            circuit.signal("c"),
+            Err(CircuitError::UndefinedWire {
+                wire: "q".to_string()
+            })
+        );
+    }
+}
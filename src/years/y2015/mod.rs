@@ -1 +1,2 @@
+pub mod circuit;
 pub mod mods;
@@ -1,3 +1,4 @@
+use crate::utils::ocr::recognize;
 use rand;
 use std::collections::HashSet;
 
@@ -13,6 +14,7 @@ pub fn part_one() {
     let mut pixels: HashSet<(usize, usize)> = HashSet::new();
     for instruction in instructions {
         pixels.process_instruction(instruction, &screen_dimensions);
+        pixels.print_on(&screen_dimensions);
     }
 
     println!("part_one={:#?}", pixels.len());
@@ -179,7 +181,6 @@ impl ProcessInstruction for Pixels {
                 }
             }
         }
-        self.print_on(&screen_dimensions);
     }
 }
 
@@ -191,6 +192,17 @@ struct ScreenDimensions {
 #[allow(dead_code)]
 pub fn part_two() {
     let start = std::time::Instant::now();
-    println!("part_two={:?}", 0);
+
+    let screen_dimensions = ScreenDimensions {
+        width: 50,
+        height: 6,
+    };
+    let mut pixels: Pixels = HashSet::new();
+    for instruction in instructions() {
+        pixels.process_instruction(instruction, &screen_dimensions);
+    }
+
+    let code = recognize(&pixels).unwrap_or_else(|error| panic!("{error}"));
+    println!("part_two={}", code);
     println!("runtime={:?}", start.elapsed());
 }
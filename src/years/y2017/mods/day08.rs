@@ -1,72 +1,269 @@
-use std::collections::HashMap;
+use crate::utils::asm::{Exit, Flow, Instruction, Machine, Registers};
+use crate::utils::error::{AocError, AocResult};
+use crate::utils::parse::parse_lines;
+use std::collections::BTreeMap;
+use std::fmt;
 
-#[allow(dead_code)]
-pub fn part_one() {
-    let start = std::time::Instant::now();
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Operand {
+    Register(String),
+    Literal(i64),
+}
+
+impl Operand {
+    fn parse(text: &str) -> Self {
+        match text.parse() {
+            Ok(value) => Operand::Literal(value),
+            Err(_) => Operand::Register(text.to_string()),
+        }
+    }
+
+    fn value(&self, registers: &Registers) -> i64 {
+        match self {
+            Operand::Register(name) => registers.get(name),
+            Operand::Literal(value) => *value,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Update {
+    Inc,
+    Dec,
+    Mul,
+    Div,
+    Mod,
+    Set,
+}
+
+impl Update {
+    fn parse(text: &str) -> AocResult<Self> {
+        Ok(match text {
+            "inc" => Update::Inc,
+            "dec" => Update::Dec,
+            "mul" => Update::Mul,
+            "div" => Update::Div,
+            "mod" => Update::Mod,
+            "set" => Update::Set,
+            _ => return Err(AocError::parse(text, "unknown update")),
+        })
+    }
 
-    let mut regs: HashMap<String, i32> = HashMap::new();
-    let mut high_opt: Option<i32> = None;
-
-    for line in include_str!("../inputs/day08.txt").lines() {
-        let mut parts = line.split_whitespace();
-        let reg = parts.next().expect("reg not found").to_string();
-        let dir = parts.next().expect("dir not found");
-        let d: i32 = parts
-            .next()
-            .expect("d not found")
-            .parse()
-            .expect("failed to parse to i32");
-        let _ = parts.next().expect("if not found"); // discarding this
-        let creg = parts.next().expect("creg not found").to_string();
-        let cop = parts.next().expect("cop not found");
-        let cnum: i32 = parts
-            .next()
-            .expect("cd not found")
-            .parse()
-            .expect("failed to parse to i32");
-
-        if !regs.contains_key(&creg) {
-            regs.insert(creg.clone(), 0);
+    /// Division and remainder by zero leave the register alone; `None` when
+    /// the result does not fit in an `i64`
+    fn apply(&self, current: i64, amount: i64) -> Option<i64> {
+        match self {
+            Update::Inc => current.checked_add(amount),
+            Update::Dec => current.checked_sub(amount),
+            Update::Mul => current.checked_mul(amount),
+            Update::Div | Update::Mod if amount == 0 => Some(current),
+            Update::Div => current.checked_div(amount),
+            Update::Mod => current.checked_rem_euclid(amount),
+            Update::Set => Some(amount),
         }
-        let creg_val = regs[&creg];
-        let reg_mut_val = regs.entry(reg).or_default();
-
-        let condition_met = match cop {
-            "<" => creg_val < cnum,
-            ">" => creg_val > cnum,
-            "<=" => creg_val <= cnum,
-            ">=" => creg_val >= cnum,
-            "==" => creg_val == cnum,
-            "!=" => creg_val != cnum,
-            _ => panic!("oops"),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Comparison {
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    Equal,
+    NotEqual,
+}
+
+impl Comparison {
+    fn parse(text: &str) -> AocResult<Self> {
+        Ok(match text {
+            "<" => Comparison::Less,
+            "<=" => Comparison::LessOrEqual,
+            ">" => Comparison::Greater,
+            ">=" => Comparison::GreaterOrEqual,
+            "==" => Comparison::Equal,
+            "!=" => Comparison::NotEqual,
+            _ => return Err(AocError::parse(text, "unknown comparison")),
+        })
+    }
+
+    fn holds(&self, left: i64, right: i64) -> bool {
+        match self {
+            Comparison::Less => left < right,
+            Comparison::LessOrEqual => left <= right,
+            Comparison::Greater => left > right,
+            Comparison::GreaterOrEqual => left >= right,
+            Comparison::Equal => left == right,
+            Comparison::NotEqual => left != right,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Condition {
+    left: Operand,
+    comparison: Comparison,
+    right: Operand,
+}
+
+/// `reg update amount [if left comparison right]`
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Statement {
+    target: String,
+    update: Update,
+    amount: Operand,
+    condition: Option<Condition>,
+}
+
+impl Statement {
+    fn parse(line: &str) -> AocResult<Self> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let (target, update, amount, condition) = match words.as_slice() {
+            [target, update, amount] => (target, update, amount, None),
+            [target, update, amount, "if", left, comparison, right] => (
+                target,
+                update,
+                amount,
+                Some(Condition {
+                    left: Operand::parse(left),
+                    comparison: Comparison::parse(comparison)?,
+                    right: Operand::parse(right),
+                }),
+            ),
+            _ => return Err(AocError::parse(line, "expected `reg op n [if reg cmp n]`")),
         };
+        Ok(Statement {
+            target: target.to_string(),
+            update: Update::parse(update)?,
+            amount: Operand::parse(amount),
+            condition,
+        })
+    }
+}
 
-        if condition_met {
-            match dir {
-                "inc" => *reg_mut_val += d,
-                "dec" => *reg_mut_val -= d,
-                _ => panic!("oops"),
+impl Instruction for Statement {
+    /// Halts on overflow, leaving the target as it was; the language itself
+    /// has no way to halt
+    fn execute(&self, registers: &mut Registers) -> Flow {
+        let applies = self.condition.as_ref().is_none_or(|condition| {
+            condition.comparison.holds(
+                condition.left.value(registers),
+                condition.right.value(registers),
+            )
+        });
+        let amount = self.amount.value(registers);
+        let target = registers.get_mut(&self.target);
+        if applies {
+            match self.update.apply(*target, amount) {
+                Some(value) => *target = value,
+                None => return Flow::Halt,
             }
         }
+        Flow::Next
+    }
+}
+
+/// Registers once the program has run, plus the highest value each one held
+#[derive(Debug, Clone)]
+struct Snapshot {
+    registers: Registers,
+    high_water: BTreeMap<String, i64>,
+}
 
-        if high_opt.is_none() || *reg_mut_val > high_opt.unwrap() {
-            high_opt = Some(reg_mut_val.clone());
+/// An update whose result does not fit in an `i64`
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Overflow {
+    /// 1-based line of the statement
+    line: usize,
+}
+
+impl fmt::Display for Overflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the update on line {} overflows", self.line)
+    }
+}
+
+impl std::error::Error for Overflow {}
+
+fn evaluate(program: Vec<Statement>) -> Result<Snapshot, Overflow> {
+    let mut high_water: BTreeMap<String, i64> = BTreeMap::new();
+    let mut machine = Machine::new(program);
+    loop {
+        let pc = machine.pc() as usize;
+        match machine.step() {
+            Some(Exit::Halted { pc }) => return Err(Overflow { line: pc + 1 }),
+            Some(_) => break,
+            None => (),
         }
+        let target = &machine.program()[pc].target;
+        let value = machine.registers.get(target);
+        let mark = high_water.entry(target.clone()).or_insert(value);
+        *mark = (*mark).max(value);
     }
+    Ok(Snapshot {
+        registers: machine.registers,
+        high_water,
+    })
+}
 
+fn input() -> Vec<Statement> {
+    parse_lines(include_str!("../inputs/day08.txt"), Statement::parse)
+        .unwrap_or_else(|error| panic!("{error}"))
+}
+
+#[allow(dead_code)]
+pub fn part_one() {
+    let start = std::time::Instant::now();
+    let snapshot = evaluate(input()).unwrap_or_else(|error| panic!("{error}"));
     println!(
         "part_one={:#?}",
-        regs.into_iter()
-            .max_by(|(_, a), (_, b)| a.cmp(&b))
+        snapshot
+            .registers
+            .iter()
+            .map(|(_, value)| value)
+            .max()
             .expect("max not found")
-            .1
     );
-    println!("part_two={:#?}", high_opt.expect("highest not found"));
     println!("runtime={:#?}", start.elapsed());
 }
 
 #[allow(dead_code)]
 pub fn part_two() {
-    // let start = std::time::Instant::now();
-    // println!("part_one={:#?}\nruntime={:#?}", 0, start.elapsed());
+    let start = std::time::Instant::now();
+    let snapshot = evaluate(input()).unwrap_or_else(|error| panic!("{error}"));
+    println!(
+        "part_two={:#?}",
+        snapshot
+            .high_water
+            .into_values()
+            .max()
+            .expect("highest not found")
+    );
+    println!("runtime={:#?}", start.elapsed());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evaluates_the_example() {
+        let program = parse_lines(
+            "b inc 5 if a > 1\na inc 1 if b < 5\nc dec -10 if a >= 1\nc inc -20 if c == 10",
+            Statement::parse,
+        )
+        .unwrap();
+        let snapshot = evaluate(program).unwrap();
+        assert_eq!(snapshot.registers.get("a"), 1);
+        assert_eq!(snapshot.registers.get("c"), -10);
+        assert_eq!(snapshot.high_water["c"], 10);
+        assert!(Statement::parse("a jmp 1 if b > 0").is_err());
+
+        let program = parse_lines(
+            "a inc 9223372036854775807\nb dec 1\na inc 1 if b < 0",
+            Statement::parse,
+        )
+        .unwrap();
+        assert_eq!(evaluate(program).unwrap_err(), Overflow { line: 3 });
+    }
 }
@@ -1,3 +1,5 @@
+use crate::utils::error::AocResult;
+use crate::utils::parse::{parse_lines, scan};
 use std::collections::{HashMap, HashSet};
 
 #[derive(Debug)]
@@ -11,40 +13,23 @@ struct Square {
 
 impl Square {
     #[allow(dead_code)]
-    fn new(input: String) -> Self {
-        let [id, col_start, row_start, width, height] = input
-            .to_string()
-            .replace('#', "")
-            .replace(':', "")
-            .replace(" @", "")
-            .replace('x', ",")
-            .split_whitespace()
-            .flat_map(|x| {
-                x.split(',')
-                    .map(|y| y.parse().unwrap())
-                    .collect::<Vec<i32>>()
-            })
-            .collect::<Vec<i32>>()
-            .try_into()
-            .unwrap();
+    fn new(input: &str) -> AocResult<Self> {
+        // input: "#1 @ 1,3: 4x4"
+        let (id, col_start, row_start, width, height) = scan("#{} @ {},{}: {}x{}", input)?;
 
-        Self {
+        Ok(Self {
             id,
             col_start,
             row_start,
             width,
             height,
-        }
+        })
     }
 }
 
 #[allow(dead_code)]
 fn input() -> Vec<Square> {
-    include_str!("../inputs/day03.txt")
-        .lines()
-        .map(|line| line.to_string())
-        .map(|x| Square::new(x))
-        .collect()
+    parse_lines(include_str!("../inputs/day03.txt"), Square::new).expect("failed to parse squares")
 }
 
 #[allow(dead_code)]
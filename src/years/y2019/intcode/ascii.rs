@@ -0,0 +1,149 @@
+use super::vm::{Event, Vm};
+use super::IntcodeError;
+use std::io::{self, BufRead, Write};
+
+/// Everything a program printed between two requests for input
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Reply {
+    pub text: String,
+    /// Output values outside the ASCII range, usually the puzzle answer
+    pub answers: Vec<i64>,
+    pub halted: bool,
+}
+
+impl Reply {
+    fn push(&mut self, value: i64) {
+        match u8::try_from(value) {
+            Ok(byte) if byte.is_ascii() => self.text.push(byte as char),
+            _ => self.answers.push(value),
+        }
+    }
+
+    fn append(&mut self, other: Reply) {
+        self.text.push_str(&other.text);
+        self.answers.extend(other.answers);
+        self.halted = other.halted;
+    }
+}
+
+/// Talks to an Intcode program that reads and writes lines of ASCII text
+#[derive(Debug, Clone)]
+pub struct Ascii {
+    vm: Vm,
+}
+
+impl Ascii {
+    pub fn new(vm: Vm) -> Self {
+        Self { vm }
+    }
+
+    pub fn vm(&self) -> &Vm {
+        &self.vm
+    }
+
+    /// Queues `line` followed by a newline
+    pub fn send_line(&mut self, line: &str) {
+        self.vm
+            .extend_input(line.bytes().chain([b'\n']).map(i64::from));
+    }
+
+    /// Runs until the program wants input it doesn't have, or halts
+    pub fn run(&mut self) -> Result<Reply, IntcodeError> {
+        let mut reply = Reply::default();
+        loop {
+            match self.vm.run_until_event()? {
+                Event::Output(_) => {
+                    let value = self.vm.pop_output().expect("output event without output");
+                    reply.push(value);
+                }
+                Event::NeedsInput => return Ok(reply),
+                Event::Halted => {
+                    reply.halted = true;
+                    return Ok(reply);
+                }
+            }
+        }
+    }
+
+    /// Feeds the script one line per input request and collects every reply
+    ///
+    /// Blank lines and `//` comments are skipped, and lines left over once
+    /// the program halts are ignored.
+    pub fn run_script(&mut self, script: &str) -> Result<Reply, IntcodeError> {
+        let mut reply = self.run()?;
+        let lines = script
+            .lines()
+            .map(str::trim_end)
+            .filter(|line| !line.is_empty() && !line.starts_with("//"));
+        for line in lines {
+            if reply.halted {
+                break;
+            }
+            self.send_line(line);
+            reply.append(self.run()?);
+        }
+        Ok(reply)
+    }
+
+    /// Plays the program by hand: prints its replies and sends what is typed
+    pub fn interactive(
+        &mut self,
+        mut input: impl BufRead,
+        mut output: impl Write,
+    ) -> io::Result<()> {
+        loop {
+            let reply = self.run().map_err(io::Error::other)?;
+            write!(output, "{}", reply.text)?;
+            for answer in reply.answers {
+                writeln!(output, "answer={answer}")?;
+            }
+            if reply.halted {
+                return Ok(());
+            }
+            output.flush()?;
+
+            let mut line = String::new();
+            if input.read_line(&mut line)? == 0 {
+                return Ok(());
+            }
+            self.send_line(line.trim_end_matches(['\r', '\n']));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // echoes every input value forever
+    const ECHO: [i64; 7] = [3, 100, 4, 100, 1105, 1, 0];
+
+    #[test]
+    fn splits_text_from_answers() {
+        // prints "Hi\n", then 1000, then halts
+        let program = vec![104, 72, 104, 105, 104, 10, 104, 1000, 99];
+        let reply = Ascii::new(Vm::new(program)).run().unwrap();
+        assert_eq!(
+            reply,
+            Reply {
+                text: "Hi\n".to_string(),
+                answers: vec![1000],
+                halted: true
+            }
+        );
+    }
+
+    #[test]
+    fn scripts_and_interactive_sessions_share_the_machine() {
+        let mut ascii = Ascii::new(Vm::new(ECHO.to_vec()));
+        let reply = ascii.run_script("// comment\nNOT A J\n\nWALK\n").unwrap();
+        assert_eq!(reply.text, "NOT A J\nWALK\n");
+        assert!(!reply.halted);
+
+        let mut transcript = Vec::new();
+        ascii
+            .interactive("north\n".as_bytes(), &mut transcript)
+            .unwrap();
+        assert_eq!(String::from_utf8(transcript).unwrap(), "north\n");
+    }
+}
@@ -0,0 +1,51 @@
+use super::ascii::Ascii;
+use super::debugger::{repl, Debugger};
+use super::disasm::listing;
+use super::parse_program;
+use super::vm::Vm;
+use std::error::Error;
+use std::io;
+
+const USAGE: &str = "\
+usage: aoc intcode disasm <file>
+       aoc intcode debug <file>
+       aoc intcode ascii <file> [script]";
+
+fn read_program(path: &str) -> Result<Vec<i64>, Box<dyn Error>> {
+    let text =
+        std::fs::read_to_string(path).map_err(|error| format!("failed to read {path}: {error}"))?;
+    Ok(parse_program(&text)?)
+}
+
+/// Entry point for `aoc intcode ...`
+pub fn run(args: &[String]) -> Result<(), Box<dyn Error>> {
+    match args {
+        [command, path] if command == "disasm" => {
+            println!("{}", listing(&read_program(path)?));
+            Ok(())
+        }
+        [command, path] if command == "debug" => {
+            let mut debugger = Debugger::new(Vm::new(read_program(path)?));
+            repl(&mut debugger, io::stdin().lock(), io::stdout())?;
+            Ok(())
+        }
+        [command, path, script @ ..] if command == "ascii" && script.len() <= 1 => {
+            let mut ascii = Ascii::new(Vm::new(read_program(path)?));
+            if let Some(script) = script.first() {
+                let text = std::fs::read_to_string(script)
+                    .map_err(|error| format!("failed to read {script}: {error}"))?;
+                let reply = ascii.run_script(&text)?;
+                print!("{}", reply.text);
+                for answer in reply.answers {
+                    println!("answer={answer}");
+                }
+                if reply.halted {
+                    return Ok(());
+                }
+            }
+            ascii.interactive(io::stdin().lock(), io::stdout())?;
+            Ok(())
+        }
+        _ => Err(USAGE.into()),
+    }
+}
@@ -0,0 +1,432 @@
+use super::decode::Opcode;
+use super::disasm::line_at;
+use super::vm::{Event, Vm};
+use super::IntcodeError;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::{self, BufRead, Write};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Breakpoint {
+    /// Stop before executing the instruction at this address
+    Address(usize),
+    /// Stop before executing any instruction with this opcode
+    Opcode(Opcode),
+}
+
+impl fmt::Display for Breakpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Breakpoint::Address(address) => write!(f, "address {address}"),
+            Breakpoint::Opcode(opcode) => write!(f, "opcode {}", opcode.mnemonic()),
+        }
+    }
+}
+
+/// Why execution stopped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stop {
+    Breakpoint(Breakpoint),
+    Watch {
+        address: usize,
+        old: i64,
+        new: i64,
+    },
+    /// Only reported when [`Debugger::break_on_output`] is set
+    Output(i64),
+    NeedsInput,
+    Halted,
+    /// A single step finished without anything noteworthy happening
+    Stepped,
+}
+
+impl fmt::Display for Stop {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Stop::Breakpoint(breakpoint) => write!(f, "hit breakpoint on {breakpoint}"),
+            Stop::Watch { address, old, new } => {
+                write!(f, "watched [{address}] changed {old} -> {new}")
+            }
+            Stop::Output(value) => write!(f, "output {value}"),
+            Stop::NeedsInput => write!(f, "waiting for input"),
+            Stop::Halted => write!(f, "halted"),
+            Stop::Stepped => write!(f, "stepped"),
+        }
+    }
+}
+
+/// One executed instruction, captured just before it ran
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceEntry {
+    pub step: usize,
+    pub ip: usize,
+    pub relative_base: i64,
+    pub instruction: String,
+}
+
+impl fmt::Display for TraceEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:>8} rb={:<6} {}",
+            self.step,
+            self.relative_base,
+            self.instruction.trim_start()
+        )
+    }
+}
+
+/// Wraps a [`Vm`] with breakpoints, memory watches and an execution trace
+#[derive(Debug, Clone)]
+pub struct Debugger {
+    vm: Vm,
+    breakpoints: Vec<Breakpoint>,
+    watches: BTreeMap<usize, i64>,
+    trace: Option<Vec<TraceEntry>>,
+    steps: usize,
+    pub break_on_output: bool,
+}
+
+impl Debugger {
+    pub fn new(vm: Vm) -> Self {
+        Self {
+            vm,
+            breakpoints: Vec::new(),
+            watches: BTreeMap::new(),
+            trace: None,
+            steps: 0,
+            break_on_output: false,
+        }
+    }
+
+    pub fn vm(&self) -> &Vm {
+        &self.vm
+    }
+
+    pub fn vm_mut(&mut self) -> &mut Vm {
+        &mut self.vm
+    }
+
+    /// Instructions executed so far
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    pub fn add_breakpoint(&mut self, breakpoint: Breakpoint) {
+        if !self.breakpoints.contains(&breakpoint) {
+            self.breakpoints.push(breakpoint);
+        }
+    }
+
+    pub fn remove_breakpoint(&mut self, breakpoint: Breakpoint) {
+        self.breakpoints.retain(|existing| *existing != breakpoint);
+    }
+
+    pub fn breakpoints(&self) -> &[Breakpoint] {
+        &self.breakpoints
+    }
+
+    pub fn watch(&mut self, address: usize) {
+        self.watches.insert(address, self.vm.read(address));
+    }
+
+    pub fn unwatch(&mut self, address: usize) {
+        self.watches.remove(&address);
+    }
+
+    pub fn watches(&self) -> &BTreeMap<usize, i64> {
+        &self.watches
+    }
+
+    /// Starts recording every executed instruction, keeping any earlier trace
+    pub fn enable_trace(&mut self) {
+        self.trace.get_or_insert_with(Vec::new);
+    }
+
+    pub fn disable_trace(&mut self) {
+        self.trace = None;
+    }
+
+    pub fn trace(&self) -> &[TraceEntry] {
+        self.trace.as_deref().unwrap_or_default()
+    }
+
+    pub fn dump_trace(&self, path: &str) -> io::Result<()> {
+        let mut file = io::BufWriter::new(std::fs::File::create(path)?);
+        for entry in self.trace() {
+            writeln!(file, "{entry}")?;
+        }
+        file.flush()
+    }
+
+    fn breakpoint_here(&self) -> Option<Breakpoint> {
+        let ip = self.vm.ip();
+        let opcode = self.vm.current_instruction().ok().map(|i| i.opcode);
+        self.breakpoints
+            .iter()
+            .copied()
+            .find(|breakpoint| match breakpoint {
+                Breakpoint::Address(address) => *address == ip,
+                Breakpoint::Opcode(target) => Some(*target) == opcode,
+            })
+    }
+
+    /// Executes one instruction regardless of breakpoints
+    pub fn step(&mut self) -> Result<Stop, IntcodeError> {
+        let executes = !self.vm.is_halted();
+        let entry = self.trace.is_some().then(|| TraceEntry {
+            step: self.steps,
+            ip: self.vm.ip(),
+            relative_base: self.vm.relative_base(),
+            instruction: line_at(self.vm.memory(), self.vm.ip()).to_string(),
+        });
+
+        let event = self.vm.step()?;
+        if event == Some(Event::NeedsInput) {
+            return Ok(Stop::NeedsInput);
+        }
+        if executes {
+            self.steps += 1;
+            if let (Some(trace), Some(entry)) = (self.trace.as_mut(), entry) {
+                trace.push(entry);
+            }
+        }
+        if event == Some(Event::Halted) {
+            return Ok(Stop::Halted);
+        }
+
+        for (address, last) in self.watches.iter_mut() {
+            let new = self.vm.read(*address);
+            if new != *last {
+                let old = std::mem::replace(last, new);
+                return Ok(Stop::Watch {
+                    address: *address,
+                    old,
+                    new,
+                });
+            }
+        }
+        match event {
+            Some(Event::Output(value)) if self.break_on_output => Ok(Stop::Output(value)),
+            _ => Ok(Stop::Stepped),
+        }
+    }
+
+    /// Runs until a breakpoint, watch, input request or halt
+    ///
+    /// The instruction under the instruction pointer always runs first, so
+    /// continuing from a breakpoint moves past it.
+    pub fn resume(&mut self) -> Result<Stop, IntcodeError> {
+        let mut first = true;
+        loop {
+            if !first {
+                if let Some(breakpoint) = self.breakpoint_here() {
+                    return Ok(Stop::Breakpoint(breakpoint));
+                }
+            }
+            first = false;
+            match self.step()? {
+                Stop::Stepped => continue,
+                stop => return Ok(stop),
+            }
+        }
+    }
+}
+
+const HELP: &str = "\
+commands:
+  s|step [n]            execute n instructions (default 1)
+  c|continue            run to the next breakpoint, watch, input request or halt
+  b|break <addr|op>     break on an address or a mnemonic such as `out`
+  d|delete <addr|op>    remove a breakpoint
+  w|watch <addr>        stop whenever a memory cell changes
+  u|unwatch <addr>      stop watching a memory cell
+  p|print <addr> [n]    show n memory cells starting at addr
+  l|list [n]            disassemble n instructions from the instruction pointer
+  i|info                instruction pointer, relative base, queues, breakpoints
+  in|input <v>...       queue input values
+  out|output            collect queued output
+  trace on|off|dump <file>
+  q|quit";
+
+fn parse_breakpoint(arg: &str) -> Option<Breakpoint> {
+    match arg.parse::<usize>() {
+        Ok(address) => Some(Breakpoint::Address(address)),
+        Err(_) => Opcode::from_mnemonic(arg).map(Breakpoint::Opcode),
+    }
+}
+
+/// Line-based debugger shell reading commands from `input`
+///
+/// Works the same over stdin/stdout and over in-memory buffers in tests.
+pub fn repl(
+    debugger: &mut Debugger,
+    input: impl BufRead,
+    mut output: impl Write,
+) -> io::Result<()> {
+    writeln!(
+        output,
+        "{}",
+        line_at(debugger.vm().memory(), debugger.vm().ip())
+    )?;
+    for line in input.lines() {
+        let line = line?;
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let Some((command, args)) = words.split_first() else {
+            continue;
+        };
+        let number = |i: usize, default: usize| -> usize {
+            args.get(i)
+                .and_then(|arg| arg.parse().ok())
+                .unwrap_or(default)
+        };
+
+        match *command {
+            "s" | "step" | "c" | "continue" => {
+                let result = if command.starts_with('c') {
+                    debugger.resume().map(|stop| vec![stop])
+                } else {
+                    (0..number(0, 1))
+                        .map(|_| debugger.step())
+                        .collect::<Result<Vec<Stop>, IntcodeError>>()
+                };
+                match result {
+                    Ok(stops) => {
+                        for stop in stops.iter().filter(|stop| **stop != Stop::Stepped) {
+                            writeln!(output, "{stop}")?;
+                        }
+                    }
+                    Err(error) => writeln!(output, "error: {error}")?,
+                }
+                writeln!(
+                    output,
+                    "{}",
+                    line_at(debugger.vm().memory(), debugger.vm().ip())
+                )?;
+            }
+            "b" | "break" | "d" | "delete" => {
+                match args.first().and_then(|arg| parse_breakpoint(arg)) {
+                    Some(breakpoint) if command.starts_with('b') => {
+                        debugger.add_breakpoint(breakpoint);
+                        writeln!(output, "breakpoint on {breakpoint}")?;
+                    }
+                    Some(breakpoint) => debugger.remove_breakpoint(breakpoint),
+                    None => writeln!(output, "expected an address or mnemonic")?,
+                }
+            }
+            "w" | "watch" | "u" | "unwatch" => match args.first().and_then(|a| a.parse().ok()) {
+                Some(address) if command.starts_with('w') => debugger.watch(address),
+                Some(address) => debugger.unwatch(address),
+                None => writeln!(output, "expected an address")?,
+            },
+            "p" | "print" => {
+                let start = number(0, debugger.vm().ip());
+                let cells: Vec<String> = (start..start + number(1, 1))
+                    .map(|address| debugger.vm().read(address).to_string())
+                    .collect();
+                writeln!(output, "[{start}] {}", cells.join(" "))?;
+            }
+            "l" | "list" => {
+                let mut address = debugger.vm().ip();
+                for _ in 0..number(0, 5) {
+                    let line = line_at(debugger.vm().memory(), address);
+                    address += match &line.item {
+                        super::disasm::Item::Code { params, .. } => params.len() + 1,
+                        super::disasm::Item::Data(_) => 1,
+                    };
+                    writeln!(output, "{line}")?;
+                }
+            }
+            "i" | "info" => {
+                let vm = debugger.vm();
+                writeln!(
+                    output,
+                    "ip={} rb={} steps={} halted={}",
+                    vm.ip(),
+                    vm.relative_base(),
+                    debugger.steps(),
+                    vm.is_halted()
+                )?;
+                writeln!(output, "input={:?}", vm.pending_input())?;
+                writeln!(output, "output={:?}", vm.pending_output())?;
+                for breakpoint in debugger.breakpoints() {
+                    writeln!(output, "breakpoint on {breakpoint}")?;
+                }
+                for (address, value) in debugger.watches() {
+                    writeln!(output, "watching [{address}] = {value}")?;
+                }
+            }
+            "in" | "input" => {
+                let values: Vec<i64> = args.iter().filter_map(|arg| arg.parse().ok()).collect();
+                debugger.vm_mut().extend_input(values);
+            }
+            "out" | "output" => writeln!(output, "{:?}", debugger.vm_mut().take_outputs())?,
+            "trace" => match args.first().copied() {
+                Some("on") => debugger.enable_trace(),
+                Some("off") => debugger.disable_trace(),
+                Some("dump") => match args.get(1) {
+                    Some(path) => {
+                        debugger.dump_trace(path)?;
+                        writeln!(output, "wrote {} entries to {path}", debugger.trace().len())?;
+                    }
+                    None => writeln!(output, "expected a file name")?,
+                },
+                _ => writeln!(output, "expected on, off or dump <file>")?,
+            },
+            "q" | "quit" => break,
+            _ => writeln!(output, "{HELP}")?,
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // adds 1 to [20] three times, outputting each value, then halts
+    fn counter() -> Vm {
+        Vm::new(vec![
+            1001, 20, 1, 20, 4, 20, 1001, 20, 1, 20, 4, 20, 1001, 20, 1, 20, 4, 20, 99, 0, 0,
+        ])
+    }
+
+    #[test]
+    fn stops_on_breakpoints_and_watches() {
+        let mut debugger = Debugger::new(counter());
+        debugger.add_breakpoint(Breakpoint::Opcode(Opcode::Output));
+        assert_eq!(
+            debugger.resume().unwrap(),
+            Stop::Breakpoint(Breakpoint::Opcode(Opcode::Output))
+        );
+        assert_eq!(debugger.vm().ip(), 4);
+
+        debugger.remove_breakpoint(Breakpoint::Opcode(Opcode::Output));
+        debugger.watch(20);
+        assert_eq!(
+            debugger.resume().unwrap(),
+            Stop::Watch {
+                address: 20,
+                old: 1,
+                new: 2
+            }
+        );
+        debugger.unwatch(20);
+        assert_eq!(debugger.resume().unwrap(), Stop::Halted);
+        assert_eq!(debugger.vm_mut().take_outputs(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn repl_drives_the_machine() {
+        let mut debugger = Debugger::new(counter());
+        let commands = "trace on\nb 12\nc\ni\nstep 2\nq\n";
+        let mut transcript = Vec::new();
+        repl(&mut debugger, commands.as_bytes(), &mut transcript).unwrap();
+
+        let transcript = String::from_utf8(transcript).unwrap();
+        assert!(transcript.contains("hit breakpoint on address 12"));
+        assert!(transcript.contains("ip=12 rb=0 steps=4"));
+        assert_eq!(debugger.trace().len(), 6);
+        assert_eq!(debugger.trace()[4].ip, 12);
+    }
+}
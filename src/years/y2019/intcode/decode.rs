@@ -0,0 +1,147 @@
+use super::IntcodeError;
+
+/// How a parameter is interpreted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// value is an address
+    Position,
+    /// value is used as is
+    Immediate,
+    /// value is an offset from the relative base
+    Relative,
+}
+
+impl Mode {
+    fn from_digit(digit: i64, address: usize, value: i64) -> Result<Self, IntcodeError> {
+        match digit {
+            0 => Ok(Mode::Position),
+            1 => Ok(Mode::Immediate),
+            2 => Ok(Mode::Relative),
+            _ => Err(IntcodeError::InvalidMode { address, value }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Opcode {
+    Add,
+    Multiply,
+    Input,
+    Output,
+    JumpIfTrue,
+    JumpIfFalse,
+    LessThan,
+    Equals,
+    AdjustRelativeBase,
+    Halt,
+}
+
+impl Opcode {
+    pub const ALL: [Opcode; 10] = [
+        Opcode::Add,
+        Opcode::Multiply,
+        Opcode::Input,
+        Opcode::Output,
+        Opcode::JumpIfTrue,
+        Opcode::JumpIfFalse,
+        Opcode::LessThan,
+        Opcode::Equals,
+        Opcode::AdjustRelativeBase,
+        Opcode::Halt,
+    ];
+
+    fn from_number(number: i64, address: usize, value: i64) -> Result<Self, IntcodeError> {
+        match number {
+            1 => Ok(Opcode::Add),
+            2 => Ok(Opcode::Multiply),
+            3 => Ok(Opcode::Input),
+            4 => Ok(Opcode::Output),
+            5 => Ok(Opcode::JumpIfTrue),
+            6 => Ok(Opcode::JumpIfFalse),
+            7 => Ok(Opcode::LessThan),
+            8 => Ok(Opcode::Equals),
+            9 => Ok(Opcode::AdjustRelativeBase),
+            99 => Ok(Opcode::Halt),
+            _ => Err(IntcodeError::InvalidOpcode { address, value }),
+        }
+    }
+
+    /// Number of parameters following the opcode
+    pub fn param_count(&self) -> usize {
+        match self {
+            Opcode::Add | Opcode::Multiply | Opcode::LessThan | Opcode::Equals => 3,
+            Opcode::JumpIfTrue | Opcode::JumpIfFalse => 2,
+            Opcode::Input | Opcode::Output | Opcode::AdjustRelativeBase => 1,
+            Opcode::Halt => 0,
+        }
+    }
+
+    /// Short name used by the disassembler and debugger
+    pub fn mnemonic(&self) -> &'static str {
+        match self {
+            Opcode::Add => "add",
+            Opcode::Multiply => "mul",
+            Opcode::Input => "in",
+            Opcode::Output => "out",
+            Opcode::JumpIfTrue => "jnz",
+            Opcode::JumpIfFalse => "jz",
+            Opcode::LessThan => "lt",
+            Opcode::Equals => "eq",
+            Opcode::AdjustRelativeBase => "arb",
+            Opcode::Halt => "hlt",
+        }
+    }
+
+    pub fn from_mnemonic(mnemonic: &str) -> Option<Self> {
+        Opcode::ALL
+            .into_iter()
+            .find(|opcode| opcode.mnemonic() == mnemonic)
+    }
+
+    /// Index of the parameter this opcode writes to, if any
+    pub fn write_param(&self) -> Option<usize> {
+        match self {
+            Opcode::Add | Opcode::Multiply | Opcode::LessThan | Opcode::Equals => Some(2),
+            Opcode::Input => Some(0),
+            _ => None,
+        }
+    }
+}
+
+/// Opcode plus the mode of each of its parameters
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Instruction {
+    pub opcode: Opcode,
+    pub modes: [Mode; 3],
+}
+
+impl Instruction {
+    /// Length in memory cells including the opcode itself
+    pub fn size(&self) -> usize {
+        1 + self.opcode.param_count()
+    }
+}
+
+/// Splits e.g. `1002` into `Multiply` with modes `[Position, Immediate, Position]`
+///
+/// `address` is only used for error reporting.
+pub fn decode(value: i64, address: usize) -> Result<Instruction, IntcodeError> {
+    if value < 0 {
+        return Err(IntcodeError::InvalidOpcode { address, value });
+    }
+    let opcode = Opcode::from_number(value % 100, address, value)?;
+    if value / 100_000 != 0 {
+        return Err(IntcodeError::InvalidMode { address, value });
+    }
+    let modes = [
+        Mode::from_digit(value / 100 % 10, address, value)?,
+        Mode::from_digit(value / 1_000 % 10, address, value)?,
+        Mode::from_digit(value / 10_000 % 10, address, value)?,
+    ];
+    if let Some(param) = opcode.write_param() {
+        if modes[param] == Mode::Immediate {
+            return Err(IntcodeError::ImmediateWrite { address });
+        }
+    }
+    Ok(Instruction { opcode, modes })
+}
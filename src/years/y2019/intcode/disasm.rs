@@ -0,0 +1,221 @@
+use super::decode::{decode, Instruction, Mode, Opcode};
+use std::collections::{BTreeSet, VecDeque};
+use std::fmt;
+
+/// One decoded stretch of memory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Item {
+    Code {
+        instruction: Instruction,
+        params: Vec<i64>,
+    },
+    Data(i64),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Line {
+    pub address: usize,
+    pub item: Item,
+    /// Some reachable jump lands here
+    pub jump_target: bool,
+    /// Found by following control flow rather than by the linear sweep
+    pub reached: bool,
+}
+
+/// `[12]` for position, `#12` for immediate and `[rb+12]` for relative
+fn format_param(mode: Mode, value: i64) -> String {
+    match mode {
+        Mode::Position => format!("[{value}]"),
+        Mode::Immediate => format!("#{value}"),
+        Mode::Relative if value < 0 => format!("[rb{value}]"),
+        Mode::Relative => format!("[rb+{value}]"),
+    }
+}
+
+impl fmt::Display for Line {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.jump_target {
+            writeln!(f, "L{:04}:", self.address)?;
+        }
+        match &self.item {
+            Item::Code {
+                instruction,
+                params,
+            } => {
+                let operands = params
+                    .iter()
+                    .zip(instruction.modes)
+                    .map(|(value, mode)| format_param(mode, *value))
+                    .collect::<Vec<String>>()
+                    .join(", ");
+                let text = format!(
+                    "  {:04}  {:<4} {}",
+                    self.address,
+                    instruction.opcode.mnemonic(),
+                    operands
+                );
+                write!(f, "{}", text.trim_end())?;
+                let is_jump =
+                    matches!(instruction.opcode, Opcode::JumpIfTrue | Opcode::JumpIfFalse);
+                if is_jump && instruction.modes[1] == Mode::Immediate {
+                    write!(f, "  -> L{:04}", params[1])?;
+                }
+                if !self.reached {
+                    write!(f, "  ; unreached")?;
+                }
+                Ok(())
+            }
+            Item::Data(value) => write!(f, "  {:04}  .data {}", self.address, value),
+        }
+    }
+}
+
+/// Decodes whatever sits at `address` as an instruction, or as data if it can't be
+pub fn line_at(memory: &[i64], address: usize) -> Line {
+    let value = memory.get(address).copied().unwrap_or(0);
+    let item = match decode(value, address) {
+        Ok(instruction) => Item::Code {
+            instruction,
+            params: (1..instruction.size())
+                .map(|offset| memory.get(address + offset).copied().unwrap_or(0))
+                .collect(),
+        },
+        Err(_) => Item::Data(value),
+    };
+    Line {
+        address,
+        item,
+        jump_target: false,
+        reached: true,
+    }
+}
+
+/// Decodes a program into lines of code and data
+///
+/// Code is found by following control flow from address 0: straight-line
+/// execution, both sides of every jump with an immediate target, stopping
+/// at `hlt`. Jumps through memory can't be followed statically and
+/// self-modifying programs patch cells before running them, so code only
+/// reached those ways is not found by that pass. Whatever it leaves is then
+/// decoded linearly and marked `; unreached`, and only cells that don't
+/// decode as a complete instruction are shown as data.
+pub fn disassemble(program: &[i64]) -> Vec<Line> {
+    let mut code: BTreeSet<usize> = BTreeSet::new();
+    let mut jump_targets: BTreeSet<usize> = BTreeSet::new();
+    let mut pending: VecDeque<usize> = VecDeque::from([0]);
+
+    while let Some(mut address) = pending.pop_front() {
+        while address < program.len() && !code.contains(&address) {
+            let Ok(instruction) = decode(program[address], address) else {
+                break;
+            };
+            if address + instruction.size() > program.len() {
+                break;
+            }
+            code.insert(address);
+
+            match instruction.opcode {
+                Opcode::JumpIfTrue | Opcode::JumpIfFalse => {
+                    let target = program[address + 2];
+                    if instruction.modes[1] == Mode::Immediate {
+                        if let Ok(target) = usize::try_from(target) {
+                            jump_targets.insert(target);
+                            pending.push_back(target);
+                        }
+                    }
+                    // an immediate condition makes the jump unconditional one way or the other
+                    if instruction.modes[0] == Mode::Immediate {
+                        let taken = (program[address + 1] != 0)
+                            == (instruction.opcode == Opcode::JumpIfTrue);
+                        if taken {
+                            break;
+                        }
+                    }
+                }
+                Opcode::Halt => break,
+                _ => (),
+            }
+            address += instruction.size();
+        }
+    }
+
+    let mut lines = Vec::new();
+    let mut address = 0;
+    while address < program.len() {
+        let jump_target = jump_targets.contains(&address);
+        let reached = code.contains(&address);
+        // an unreached instruction must not run into reached code
+        let instruction = decode(program[address], address)
+            .ok()
+            .filter(|instruction| {
+                let end = address + instruction.size();
+                end <= program.len() && (reached || code.range(address..end).next().is_none())
+            });
+        match instruction {
+            Some(instruction) => {
+                let params = program[address + 1..address + instruction.size()].to_vec();
+                lines.push(Line {
+                    address,
+                    item: Item::Code {
+                        instruction,
+                        params,
+                    },
+                    jump_target,
+                    reached,
+                });
+                address += instruction.size();
+            }
+            None => {
+                lines.push(Line {
+                    address,
+                    item: Item::Data(program[address]),
+                    jump_target,
+                    reached: false,
+                });
+                address += 1;
+            }
+        }
+    }
+    lines
+}
+
+/// Disassembly as printable text, one line per instruction or data cell
+pub fn listing(program: &[i64]) -> String {
+    disassemble(program)
+        .iter()
+        .map(|line| line.to_string())
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn separates_code_from_data_and_marks_targets() {
+        // input, jump past a data cell when non-zero, output, halt
+        let program = vec![3, 20, 1005, 20, 6, 42, 204, -1, 99];
+        let expected = "  0000  in   [20]
+  0002  jnz  [20], #6  -> L0006
+  0005  .data 42
+L0006:
+  0006  out  [rb-1]
+  0008  hlt";
+        assert_eq!(listing(&program), expected);
+    }
+
+    #[test]
+    fn decodes_unreached_regions_linearly() {
+        // flow stops at the first hlt; the zeros after it don't decode
+        let program = vec![1101, 4, 0, 6, 99, 99, 0, 0, 1101, 1, 2, 0, 99];
+        let expected = "  0000  add  #4, #0, [6]
+  0004  hlt
+  0005  hlt  ; unreached
+  0006  .data 0
+  0007  .data 0
+  0008  add  #1, #2, [0]  ; unreached
+  0012  hlt  ; unreached";
+        assert_eq!(listing(&program), expected);
+    }
+}
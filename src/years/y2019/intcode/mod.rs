@@ -0,0 +1,86 @@
+//! Shared Intcode machine used by the 2019 puzzles
+//!
+//! `decode` turns raw memory cells into instructions, `vm` executes them
+//! and `network` wires several machines together. `ascii` wraps a machine
+//! that talks in lines of text. `disasm`, `debugger` and `cli` back the
+//! `aoc intcode ...` debugging commands.
+
+pub mod ascii;
+pub mod cli;
+pub mod debugger;
+pub mod decode;
+pub mod disasm;
+pub mod network;
+pub mod vm;
+
+use crate::utils::error::{AocError, AocResult};
+use std::fmt;
+
+/// Reads a comma separated program such as `"1,9,10,3,2,3,11,0,99,30,40,50"`
+///
+/// Every cell must be an integer, so a typo is reported with its position
+/// rather than dropped and shifting every later address.
+pub fn parse_program(text: &str) -> AocResult<Vec<i64>> {
+    text.trim()
+        .split(',')
+        .enumerate()
+        .map(|(address, cell)| {
+            let cell = cell.trim();
+            cell.parse()
+                .map_err(|_| AocError::parse(cell, format!("cell {address} is not an integer")))
+        })
+        .collect()
+}
+
+/// Everything that can go wrong while decoding or running a program
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IntcodeError {
+    InvalidOpcode { address: usize, value: i64 },
+    InvalidMode { address: usize, value: i64 },
+    InvalidAddress { address: usize, target: i64 },
+    ImmediateWrite { address: usize },
+    MissingInput { address: usize },
+    Overflow { address: usize },
+}
+
+impl fmt::Display for IntcodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IntcodeError::InvalidOpcode { address, value } => {
+                write!(f, "invalid opcode in {value} at address {address}")
+            }
+            IntcodeError::InvalidMode { address, value } => {
+                write!(f, "invalid parameter mode in {value} at address {address}")
+            }
+            IntcodeError::InvalidAddress { address, target } => {
+                write!(f, "instruction at {address} refers to address {target}")
+            }
+            IntcodeError::ImmediateWrite { address } => {
+                write!(
+                    f,
+                    "instruction at {address} writes to an immediate parameter"
+                )
+            }
+            IntcodeError::MissingInput { address } => {
+                write!(f, "instruction at {address} needs input but none is left")
+            }
+            IntcodeError::Overflow { address } => {
+                write!(f, "instruction at {address} overflows a 64-bit integer")
+            }
+        }
+    }
+}
+
+impl std::error::Error for IntcodeError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_programs_strictly() {
+        assert_eq!(parse_program("1,0, -3,99\n").unwrap(), vec![1, 0, -3, 99]);
+        let error = parse_program("1,0,x,0,99").unwrap_err();
+        assert_eq!(error, AocError::parse("x", "cell 2 is not an integer"));
+    }
+}
@@ -0,0 +1,385 @@
+use super::vm::{Event, Vm};
+use super::IntcodeError;
+use std::collections::VecDeque;
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+
+/// What became of one machine once the network stopped
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MachineReport {
+    /// Every value the machine output, in order
+    pub outputs: Vec<i64>,
+    pub halted: bool,
+    pub ip: usize,
+    pub relative_base: i64,
+    /// Input that was delivered but never read
+    pub pending_input: Vec<i64>,
+}
+
+impl MachineReport {
+    fn from_vm(vm: &Vm, outputs: Vec<i64>) -> Self {
+        Self {
+            outputs,
+            halted: vm.is_halted(),
+            ip: vm.ip(),
+            relative_base: vm.relative_base(),
+            pending_input: vm.pending_input().iter().copied().collect(),
+        }
+    }
+}
+
+/// Machines whose outputs are piped into other machines' inputs
+///
+/// Each output of machine `i` is copied to the input of every machine in
+/// `links[i]`. Output with no link is still recorded in the report.
+#[derive(Debug, Clone)]
+pub struct Network {
+    machines: Vec<Vm>,
+    links: Vec<Vec<usize>>,
+}
+
+impl Network {
+    /// Unconnected machines, wire them up with [`Network::connect`]
+    pub fn new(machines: Vec<Vm>) -> Self {
+        let links = vec![Vec::new(); machines.len()];
+        Self { machines, links }
+    }
+
+    /// `0 -> 1 -> ... -> n-1`, the last machine's output leaves the network
+    pub fn chain(machines: Vec<Vm>) -> Self {
+        let mut network = Self::new(machines);
+        for i in 1..network.machines.len() {
+            network.connect(i - 1, i);
+        }
+        network
+    }
+
+    /// `0 -> 1 -> ... -> n-1 -> 0`
+    pub fn feedback_loop(machines: Vec<Vm>) -> Self {
+        let mut network = Self::chain(machines);
+        let last = network.machines.len().saturating_sub(1);
+        network.connect(last, 0);
+        network
+    }
+
+    pub fn connect(&mut self, from: usize, to: usize) {
+        self.links[from].push(to);
+    }
+
+    pub fn push_input(&mut self, machine: usize, value: i64) {
+        self.machines[machine].push_input(value);
+    }
+
+    /// Runs each machine in turn until it blocks, forwarding output as it goes
+    ///
+    /// Stops once every machine has halted or is waiting on input nobody
+    /// will send.
+    pub fn run_round_robin(&mut self) -> Result<Vec<MachineReport>, IntcodeError> {
+        let mut outputs = vec![Vec::new(); self.machines.len()];
+        loop {
+            let mut progressed = false;
+            for (i, recorded) in outputs.iter_mut().enumerate() {
+                loop {
+                    let before = self.machines[i].ip();
+                    match self.machines[i].run_until_event()? {
+                        Event::Output(value) => {
+                            self.machines[i].pop_output();
+                            recorded.push(value);
+                            for &target in &self.links[i] {
+                                self.machines[target].push_input(value);
+                            }
+                            progressed = true;
+                        }
+                        Event::NeedsInput | Event::Halted => {
+                            progressed |= self.machines[i].ip() != before;
+                            break;
+                        }
+                    }
+                }
+            }
+            if !progressed {
+                break;
+            }
+        }
+
+        Ok(self
+            .machines
+            .iter()
+            .zip(outputs)
+            .map(|(vm, outputs)| MachineReport::from_vm(vm, outputs))
+            .collect())
+    }
+
+    /// Runs every machine on its own thread, blocking on input channels
+    ///
+    /// A machine that sends to a halted neighbour simply loses that value.
+    /// Once every machine still running is blocked on input and no value is
+    /// on its way to any of them, the blocked machines are told to stop.
+    pub fn run_threaded(self) -> Result<Vec<MachineReport>, IntcodeError> {
+        let count = self.machines.len();
+        let (senders, receivers): (Vec<_>, Vec<_>) =
+            (0..count).map(|_| mpsc::channel::<Option<i64>>()).unzip();
+        let activity = Arc::new(Mutex::new(Activity {
+            running: count,
+            in_flight: 0,
+            senders,
+        }));
+
+        let handles: Vec<_> = self
+            .machines
+            .into_iter()
+            .zip(receivers)
+            .zip(self.links)
+            .map(|((mut vm, receiver), links)| {
+                let activity = Arc::clone(&activity);
+                thread::spawn(move || -> Result<MachineReport, IntcodeError> {
+                    let mut outputs = Vec::new();
+                    let mut running = true;
+                    let result = (|| loop {
+                        match vm.run_until_event()? {
+                            Event::Output(value) => {
+                                vm.pop_output();
+                                outputs.push(value);
+                                activity.lock().unwrap().send(&links, value);
+                            }
+                            Event::NeedsInput => {
+                                activity.lock().unwrap().block();
+                                running = false;
+                                match receiver.recv() {
+                                    Ok(Some(value)) => {
+                                        activity.lock().unwrap().wake();
+                                        running = true;
+                                        vm.push_input(value);
+                                    }
+                                    _ => return Ok(()),
+                                }
+                            }
+                            Event::Halted => return Ok(()),
+                        }
+                    })();
+                    activity.lock().unwrap().finish(running, receiver);
+                    result.map(|()| MachineReport::from_vm(&vm, outputs))
+                })
+            })
+            .collect();
+
+        handles
+            .into_iter()
+            .map(|handle| handle.join().expect("intcode thread panicked"))
+            .collect()
+    }
+}
+
+/// Bookkeeping shared by the threads of [`Network::run_threaded`]
+///
+/// Values are only sent while holding the lock, so `in_flight` always
+/// counts exactly the values sitting in some channel.
+struct Activity {
+    /// Machines neither blocked on input nor finished
+    running: usize,
+    in_flight: usize,
+    /// `None` tells a blocked machine to stop
+    senders: Vec<mpsc::Sender<Option<i64>>>,
+}
+
+impl Activity {
+    fn send(&mut self, links: &[usize], value: i64) {
+        for &to in links {
+            // fails only once the receiving machine has finished
+            if self.senders[to].send(Some(value)).is_ok() {
+                self.in_flight += 1;
+            }
+        }
+    }
+
+    fn block(&mut self) {
+        self.running -= 1;
+        self.stop_if_deadlocked();
+    }
+
+    fn wake(&mut self) {
+        self.running += 1;
+        self.in_flight -= 1;
+    }
+
+    /// Drops `receiver` under the lock, discounting values nobody will read
+    fn finish(&mut self, running: bool, receiver: mpsc::Receiver<Option<i64>>) {
+        if running {
+            self.running -= 1;
+        }
+        self.in_flight -= receiver.try_iter().flatten().count();
+        drop(receiver);
+        self.stop_if_deadlocked();
+    }
+
+    fn stop_if_deadlocked(&self) {
+        if self.running == 0 && self.in_flight == 0 {
+            for sender in &self.senders {
+                let _ = sender.send(None);
+            }
+        }
+    }
+}
+
+/// Something that happened on a [`PacketBus`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BusEvent {
+    /// A machine sent `(x, y)` to the NAT address
+    NatReceived { x: i64, y: i64 },
+    /// The network went idle and the NAT woke machine 0 with `(x, y)`
+    NatSent { x: i64, y: i64 },
+}
+
+/// Machines exchanging `(destination, x, y)` packets, as in 2019 day 23
+///
+/// Every machine is booted with its own address, reads `-1` when it has no
+/// packets waiting, and anything sent to `nat_address` is held by the NAT
+/// until the whole network goes idle.
+#[derive(Debug, Clone)]
+pub struct PacketBus {
+    machines: Vec<Vm>,
+    queues: Vec<VecDeque<(i64, i64)>>,
+    nat_address: i64,
+    nat_packet: Option<(i64, i64)>,
+}
+
+impl PacketBus {
+    pub fn new(program: &[i64], size: usize, nat_address: i64) -> Self {
+        let machines = (0..size)
+            .map(|address| {
+                let mut vm = Vm::new(program.to_vec());
+                vm.push_input(address as i64);
+                vm
+            })
+            .collect();
+        Self {
+            machines,
+            queues: vec![VecDeque::new(); size],
+            nat_address,
+            nat_packet: None,
+        }
+    }
+
+    /// Runs round robin until `stop` accepts an event, returning that event
+    ///
+    /// `None` means every machine halted before that happened.
+    pub fn run_until<F>(&mut self, mut stop: F) -> Result<Option<BusEvent>, IntcodeError>
+    where
+        F: FnMut(&BusEvent) -> bool,
+    {
+        loop {
+            let mut idle = true;
+            for i in 0..self.machines.len() {
+                // a halted machine reads nothing, so don't queue input for it
+                if self.machines[i].is_halted() {
+                    continue;
+                }
+                match self.queues[i].pop_front() {
+                    Some((x, y)) => {
+                        self.machines[i].extend_input([x, y]);
+                        idle = false;
+                    }
+                    None => self.machines[i].push_input(-1),
+                }
+
+                while let Event::Output(_) = self.machines[i].run_until_event()? {
+                    if self.machines[i].pending_output().len() < 3 {
+                        continue;
+                    }
+                    let packet = self.machines[i].take_outputs();
+                    let (destination, x, y) = (packet[0], packet[1], packet[2]);
+                    idle = false;
+                    if destination == self.nat_address {
+                        self.nat_packet = Some((x, y));
+                        let event = BusEvent::NatReceived { x, y };
+                        if stop(&event) {
+                            return Ok(Some(event));
+                        }
+                    } else if let Some(queue) = usize::try_from(destination)
+                        .ok()
+                        .and_then(|destination| self.queues.get_mut(destination))
+                    {
+                        queue.push_back((x, y));
+                    }
+                }
+            }
+
+            let queues_empty = self.queues.iter().all(|queue| queue.is_empty());
+            if let (true, true, Some((x, y))) = (idle, queues_empty, self.nat_packet) {
+                self.queues[0].push_back((x, y));
+                let event = BusEvent::NatSent { x, y };
+                if stop(&event) {
+                    return Ok(Some(event));
+                }
+            }
+            if self.machines.iter().all(|vm| vm.is_halted()) {
+                return Ok(None);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stops_threads_waiting_on_each_other() {
+        // each machine echoes one value, but both wait for the other first
+        let echo = vec![3, 10, 4, 10, 99];
+        let mut network = Network::new(vec![Vm::new(echo.clone()), Vm::new(echo)]);
+        network.connect(0, 1);
+        network.connect(1, 0);
+        let reports = network.clone().run_threaded().unwrap();
+        assert_eq!(reports, network.run_round_robin().unwrap());
+        assert!(reports
+            .iter()
+            .all(|report| !report.halted && report.ip == 0));
+
+        // with a value to start it, the echo goes round and both halt
+        let echo = vec![3, 10, 4, 10, 99];
+        let mut network = Network::feedback_loop(vec![Vm::new(echo.clone()), Vm::new(echo)]);
+        network.push_input(0, 7);
+        let reports = network.run_threaded().unwrap();
+        assert_eq!(reports[0].outputs, vec![7]);
+        assert!(reports.iter().all(|report| report.halted));
+    }
+
+    #[test]
+    fn nat_wakes_an_idle_bus() {
+        // read the address, send (7, 42) to the NAT, then poll input forever
+        let program = vec![3, 100, 104, 255, 104, 7, 104, 42, 3, 101, 1105, 1, 8];
+        let mut bus = PacketBus::new(&program, 2, 255);
+        let mut events = Vec::new();
+        let last = bus
+            .run_until(|event| {
+                events.push(*event);
+                matches!(event, BusEvent::NatSent { .. })
+            })
+            .unwrap();
+        assert_eq!(last, Some(BusEvent::NatSent { x: 7, y: 42 }));
+        assert_eq!(
+            events,
+            vec![
+                BusEvent::NatReceived { x: 7, y: 42 },
+                BusEvent::NatReceived { x: 7, y: 42 },
+                BusEvent::NatSent { x: 7, y: 42 },
+            ]
+        );
+
+        // machine 0 halts straight away, machine 1 polls and reports forever
+        let program = vec![
+            3, 100, 1005, 100, 6, 99, 3, 101, 104, 255, 104, 1, 104, 2, 1105, 1, 6,
+        ];
+        let mut bus = PacketBus::new(&program, 2, 255);
+        let mut received = 0;
+        bus.run_until(|_| {
+            received += 1;
+            received == 10
+        })
+        .unwrap();
+        assert!(bus.machines[0].is_halted());
+        // only the `-1` from the round it halted in, nothing queued since
+        assert_eq!(bus.machines[0].pending_input(), &[-1]);
+    }
+}
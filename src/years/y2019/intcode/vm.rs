@@ -0,0 +1,343 @@
+use super::decode::{decode, Instruction, Mode, Opcode};
+use super::IntcodeError;
+use std::collections::{HashMap, VecDeque};
+
+/// Why [`Vm::run_until_event`] handed control back
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    /// An input instruction found the input queue empty; it will be retried
+    /// on the next step once something has been pushed
+    NeedsInput,
+    /// A value was pushed onto the output queue
+    Output(i64),
+    Halted,
+}
+
+/// Memory grows densely up to this many cells, further writes go to a map
+const DENSE_LIMIT: usize = 1 << 20;
+
+/// Intcode machine with `i64` memory that grows on demand
+///
+/// Reading past the end of memory gives `0`; writing past it extends memory,
+/// densely up to [`DENSE_LIMIT`] cells and sparsely beyond that, so a
+/// program writing to a huge address does not allocate everything below it.
+/// Input and output go through queues so the machine can be paused whenever
+/// it wants input or produces output and resumed later.
+#[derive(Debug, Clone)]
+pub struct Vm {
+    memory: Vec<i64>,
+    /// Cells written beyond the dense part of memory
+    sparse: HashMap<usize, i64>,
+    ip: usize,
+    relative_base: i64,
+    halted: bool,
+    inputs: VecDeque<i64>,
+    outputs: VecDeque<i64>,
+}
+
+impl Vm {
+    pub fn new(program: Vec<i64>) -> Self {
+        Self {
+            memory: program,
+            sparse: HashMap::new(),
+            ip: 0,
+            relative_base: 0,
+            halted: false,
+            inputs: VecDeque::new(),
+            outputs: VecDeque::new(),
+        }
+    }
+
+    pub fn ip(&self) -> usize {
+        self.ip
+    }
+
+    pub fn relative_base(&self) -> i64 {
+        self.relative_base
+    }
+
+    pub fn is_halted(&self) -> bool {
+        self.halted
+    }
+
+    /// The dense part of memory, which holds the program and everything near it
+    pub fn memory(&self) -> &[i64] {
+        &self.memory
+    }
+
+    pub fn push_input(&mut self, value: i64) {
+        self.inputs.push_back(value);
+    }
+
+    pub fn extend_input(&mut self, values: impl IntoIterator<Item = i64>) {
+        self.inputs.extend(values);
+    }
+
+    /// Input not consumed yet, oldest first
+    pub fn pending_input(&self) -> &VecDeque<i64> {
+        &self.inputs
+    }
+
+    /// Output not collected yet, oldest first
+    pub fn pending_output(&self) -> &VecDeque<i64> {
+        &self.outputs
+    }
+
+    pub fn pop_output(&mut self) -> Option<i64> {
+        self.outputs.pop_front()
+    }
+
+    pub fn take_outputs(&mut self) -> Vec<i64> {
+        self.outputs.drain(..).collect()
+    }
+
+    pub fn read(&self, address: usize) -> i64 {
+        match self.memory.get(address) {
+            Some(value) => *value,
+            None => self.sparse.get(&address).copied().unwrap_or(0),
+        }
+    }
+
+    pub fn write(&mut self, address: usize, value: i64) {
+        if address >= self.memory.len() {
+            if address >= DENSE_LIMIT {
+                self.sparse.insert(address, value);
+                return;
+            }
+            self.memory.resize(address + 1, 0);
+        }
+        self.memory[address] = value;
+    }
+
+    /// Instruction at the instruction pointer
+    pub fn current_instruction(&self) -> Result<Instruction, IntcodeError> {
+        decode(self.read(self.ip), self.ip)
+    }
+
+    /// Address a parameter refers to in position or relative mode
+    fn param_address(
+        &self,
+        instruction: &Instruction,
+        param: usize,
+    ) -> Result<usize, IntcodeError> {
+        let raw = self.read(self.ip + 1 + param);
+        let target = match instruction.modes[param] {
+            Mode::Position => raw,
+            Mode::Relative => raw
+                .checked_add(self.relative_base)
+                .ok_or(IntcodeError::Overflow { address: self.ip })?,
+            Mode::Immediate => return Err(IntcodeError::ImmediateWrite { address: self.ip }),
+        };
+        usize::try_from(target).map_err(|_| IntcodeError::InvalidAddress {
+            address: self.ip,
+            target,
+        })
+    }
+
+    fn param_value(&self, instruction: &Instruction, param: usize) -> Result<i64, IntcodeError> {
+        match instruction.modes[param] {
+            Mode::Immediate => Ok(self.read(self.ip + 1 + param)),
+            _ => Ok(self.read(self.param_address(instruction, param)?)),
+        }
+    }
+
+    /// Executes a single instruction
+    ///
+    /// Returns the event it caused, if any. Asking for input with an empty
+    /// queue executes nothing and reports [`Event::NeedsInput`]; stepping a
+    /// halted machine keeps reporting [`Event::Halted`].
+    pub fn step(&mut self) -> Result<Option<Event>, IntcodeError> {
+        if self.halted {
+            return Ok(Some(Event::Halted));
+        }
+        let instruction = self.current_instruction()?;
+        let mut next_ip = self.ip + instruction.size();
+        let mut event = None;
+
+        match instruction.opcode {
+            Opcode::Add | Opcode::Multiply | Opcode::LessThan | Opcode::Equals => {
+                let a = self.param_value(&instruction, 0)?;
+                let b = self.param_value(&instruction, 1)?;
+                let result = match instruction.opcode {
+                    Opcode::Add => a.checked_add(b),
+                    Opcode::Multiply => a.checked_mul(b),
+                    Opcode::LessThan => Some((a < b) as i64),
+                    _ => Some((a == b) as i64),
+                }
+                .ok_or(IntcodeError::Overflow { address: self.ip })?;
+                let target = self.param_address(&instruction, 2)?;
+                self.write(target, result);
+            }
+            Opcode::Input => {
+                let Some(value) = self.inputs.pop_front() else {
+                    return Ok(Some(Event::NeedsInput));
+                };
+                let target = self.param_address(&instruction, 0)?;
+                self.write(target, value);
+            }
+            Opcode::Output => {
+                let value = self.param_value(&instruction, 0)?;
+                self.outputs.push_back(value);
+                event = Some(Event::Output(value));
+            }
+            Opcode::JumpIfTrue | Opcode::JumpIfFalse => {
+                let condition = self.param_value(&instruction, 0)? != 0;
+                if condition == (instruction.opcode == Opcode::JumpIfTrue) {
+                    let target = self.param_value(&instruction, 1)?;
+                    next_ip =
+                        usize::try_from(target).map_err(|_| IntcodeError::InvalidAddress {
+                            address: self.ip,
+                            target,
+                        })?;
+                }
+            }
+            Opcode::AdjustRelativeBase => {
+                let offset = self.param_value(&instruction, 0)?;
+                self.relative_base = self
+                    .relative_base
+                    .checked_add(offset)
+                    .ok_or(IntcodeError::Overflow { address: self.ip })?;
+            }
+            Opcode::Halt => {
+                self.halted = true;
+                return Ok(Some(Event::Halted));
+            }
+        }
+
+        self.ip = next_ip;
+        Ok(event)
+    }
+
+    /// Steps until the machine needs input, produces output or halts
+    pub fn run_until_event(&mut self) -> Result<Event, IntcodeError> {
+        loop {
+            if let Some(event) = self.step()? {
+                return Ok(event);
+            }
+        }
+    }
+
+    /// Runs until the program halts, returning everything it output
+    ///
+    /// Running out of input is an error here; drive the machine with
+    /// [`Vm::run_until_event`] when input depends on earlier output.
+    pub fn run(&mut self, inputs: &[i64]) -> Result<Vec<i64>, IntcodeError> {
+        self.extend_input(inputs.iter().copied());
+        loop {
+            match self.run_until_event()? {
+                Event::Output(_) => continue,
+                Event::NeedsInput => return Err(IntcodeError::MissingInput { address: self.ip }),
+                Event::Halted => return Ok(self.take_outputs()),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn runs_day_two_example() {
+        let mut vm = Vm::new(vec![1, 9, 10, 3, 2, 3, 11, 0, 99, 30, 40, 50]);
+        vm.run(&[]).unwrap();
+        assert_eq!(vm.read(0), 3500);
+    }
+
+    #[test]
+    fn compares_input_with_jumps() {
+        // outputs 999 below 8, 1000 for 8 and 1001 above 8
+        let program = vec![
+            3, 21, 1008, 21, 8, 20, 1005, 20, 22, 107, 8, 21, 20, 1006, 20, 31, 1106, 0, 36, 98, 0,
+            0, 1002, 21, 125, 20, 4, 20, 1105, 1, 46, 104, 999, 1105, 1, 46, 1101, 1000, 1, 20, 4,
+            20, 1105, 1, 46, 98, 99,
+        ];
+        for (input, expected) in [(7, 999), (8, 1000), (9, 1001)] {
+            assert_eq!(
+                Vm::new(program.clone()).run(&[input]).unwrap(),
+                vec![expected]
+            );
+        }
+    }
+
+    #[test]
+    fn supports_relative_mode_and_large_memory() {
+        let quine = vec![
+            109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99,
+        ];
+        assert_eq!(Vm::new(quine.clone()).run(&[]).unwrap(), quine);
+        assert_eq!(
+            Vm::new(vec![104, 1125899906842624, 99]).run(&[]).unwrap(),
+            vec![1125899906842624]
+        );
+    }
+
+    #[test]
+    fn pauses_for_input_and_output() {
+        // feedback loop example from 2019 day 7, phases 9,8,7,6,5
+        let program = vec![
+            3, 26, 1001, 26, -4, 26, 3, 27, 1002, 27, 2, 27, 1, 27, 26, 27, 4, 27, 1001, 28, -1,
+            28, 1005, 28, 6, 99, 0, 0, 5,
+        ];
+        let mut amplifiers: Vec<Vm> = [9, 8, 7, 6, 5]
+            .into_iter()
+            .map(|phase| {
+                let mut vm = Vm::new(program.clone());
+                vm.push_input(phase);
+                vm
+            })
+            .collect();
+
+        let mut signal = 0;
+        'feedback: loop {
+            for amplifier in amplifiers.iter_mut() {
+                amplifier.push_input(signal);
+                match amplifier.run_until_event().unwrap() {
+                    Event::Output(value) => signal = value,
+                    Event::Halted => break 'feedback,
+                    Event::NeedsInput => panic!("amplifier starved"),
+                }
+            }
+        }
+        assert_eq!(signal, 139629729);
+    }
+
+    #[test]
+    fn reports_bad_programs() {
+        assert_eq!(
+            Vm::new(vec![1, 0, 0, 0, 42]).run(&[]),
+            Err(IntcodeError::InvalidOpcode {
+                address: 4,
+                value: 42
+            })
+        );
+        assert_eq!(
+            Vm::new(vec![3, 0, 99]).run(&[]),
+            Err(IntcodeError::MissingInput { address: 0 })
+        );
+        assert_eq!(
+            Vm::new(vec![1101, i64::MAX, 1, 0, 99]).run(&[]),
+            Err(IntcodeError::Overflow { address: 0 })
+        );
+        assert_eq!(
+            Vm::new(vec![109, i64::MAX, 109, 1, 99]).run(&[]),
+            Err(IntcodeError::Overflow { address: 2 })
+        );
+    }
+
+    #[test]
+    fn stores_far_writes_sparsely() {
+        let mut vm = Vm::new(vec![
+            1101,
+            6,
+            7,
+            1_000_000_000_000,
+            4,
+            1_000_000_000_000,
+            99,
+        ]);
+        assert_eq!(vm.run(&[]).unwrap(), vec![13]);
+        assert_eq!(vm.memory().len(), 7);
+        assert_eq!(vm.read(1_000_000_000_000), 13);
+    }
+}
@@ -1 +1,2 @@
+pub mod intcode;
 pub mod mods;
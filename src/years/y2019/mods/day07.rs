@@ -0,0 +1,94 @@
+use crate::years::y2019::intcode::network::Network;
+use crate::years::y2019::intcode::parse_program;
+use crate::years::y2019::intcode::vm::Vm;
+use itertools::Itertools;
+
+const INPUT_FILE_PATH: &str = "src/years/y2019/inputs/day07.txt";
+
+#[allow(dead_code)]
+fn input() -> Vec<i64> {
+    let text = std::fs::read_to_string(INPUT_FILE_PATH)
+        .unwrap_or_else(|_| panic!("File not found at {INPUT_FILE_PATH}"));
+    parse_program(&text).expect("failed to parse program")
+}
+
+/// Builds one amplifier per phase setting, each primed with its phase
+fn amplifiers(program: &[i64], phases: &[i64]) -> Vec<Vm> {
+    phases
+        .iter()
+        .map(|phase| {
+            let mut vm = Vm::new(program.to_vec());
+            vm.push_input(*phase);
+            vm
+        })
+        .collect()
+}
+
+/// Last signal the final amplifier sends out
+fn thruster_signal(mut network: Network) -> i64 {
+    network.push_input(0, 0);
+    let reports = network
+        .run_round_robin()
+        .unwrap_or_else(|error| panic!("{error}"));
+    *reports
+        .last()
+        .and_then(|report| report.outputs.last())
+        .expect("no thruster signal")
+}
+
+#[allow(dead_code)]
+pub fn part_one() {
+    let start = std::time::Instant::now();
+    let program = input();
+    let best = (0..5)
+        .permutations(5)
+        .map(|phases| thruster_signal(Network::chain(amplifiers(&program, &phases))))
+        .max()
+        .expect("max not found");
+    println!("part_one={:?}", best);
+    println!("runtime={:?}", start.elapsed());
+}
+
+#[allow(dead_code)]
+pub fn part_two() {
+    let start = std::time::Instant::now();
+    let program = input();
+    let best = (5..10)
+        .permutations(5)
+        .map(|phases| thruster_signal(Network::feedback_loop(amplifiers(&program, &phases))))
+        .max()
+        .expect("max not found");
+    println!("part_two={:?}", best);
+    println!("runtime={:?}", start.elapsed());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chained_amplifiers() {
+        let program = vec![
+            3, 15, 3, 16, 1002, 16, 10, 16, 1, 16, 15, 15, 4, 15, 99, 0, 0,
+        ];
+        let network = Network::chain(amplifiers(&program, &[4, 3, 2, 1, 0]));
+        assert_eq!(thruster_signal(network), 43210);
+    }
+
+    #[test]
+    fn feedback_loop_round_robin_and_threaded_agree() {
+        let program = vec![
+            3, 26, 1001, 26, -4, 26, 3, 27, 1002, 27, 2, 27, 1, 27, 26, 27, 4, 27, 1001, 28, -1,
+            28, 1005, 28, 6, 99, 0, 0, 5,
+        ];
+        let phases = [9, 8, 7, 6, 5];
+        let network = Network::feedback_loop(amplifiers(&program, &phases));
+        assert_eq!(thruster_signal(network.clone()), 139629729);
+
+        let mut threaded = network;
+        threaded.push_input(0, 0);
+        let reports = threaded.run_threaded().unwrap();
+        assert_eq!(reports[4].outputs.last(), Some(&139629729));
+        assert!(reports.iter().all(|report| report.halted));
+    }
+}
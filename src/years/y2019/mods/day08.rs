@@ -1,3 +1,6 @@
+use crate::utils::ocr::recognize;
+use std::collections::HashSet;
+
 #[allow(dead_code)]
 pub fn part_one() {
     let start = std::time::Instant::now();
@@ -51,6 +54,26 @@ pub fn part_one() {
 #[allow(dead_code)]
 pub fn part_two() {
     let start = std::time::Instant::now();
-    println!("part_one={:?}", 0);
+    let (screen_width, screen_height) = (25, 6);
+
+    let digits = include_str!("../inputs/day08.txt")
+        .trim()
+        .chars()
+        .collect::<Box<[char]>>();
+
+    // the first layer that isn't transparent (2) decides each pixel, 1 is lit
+    let pixels: HashSet<(usize, usize)> = (0..screen_width * screen_height)
+        .filter(|i| {
+            digits
+                .chunks(screen_width * screen_height)
+                .map(|layer| layer[*i])
+                .find(|digit| *digit != '2')
+                == Some('1')
+        })
+        .map(|i| (i % screen_width, i / screen_width))
+        .collect();
+
+    let message = recognize(&pixels).unwrap_or_else(|error| panic!("{error}"));
+    println!("part_two={}", message);
     println!("runtime={:?}", start.elapsed());
 }
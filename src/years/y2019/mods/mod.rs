@@ -4,7 +4,7 @@ pub mod day03;
 pub mod day04;
 pub mod day05;
 pub mod day06;
-// pub mod day07;
+pub mod day07;
 pub mod day08;
 // pub mod day09;
 // pub mod day10;
@@ -1,6 +1,7 @@
-use std::collections::HashSet;
+use crate::utils::asm::{Exit, Flow, Instruction, LoopDetection, Machine, Registers};
+use std::collections::VecDeque;
 
-#[derive(Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Instr {
     Acc(i32),
     Jmp(i32),
@@ -20,7 +21,28 @@ impl From<&str> for Instr {
     }
 }
 
+impl Instruction for Instr {
+    fn execute(&self, registers: &mut Registers) -> Flow {
+        match self {
+            Instr::Acc(val) => {
+                *registers.get_mut("acc") += *val as i64;
+                Flow::Next
+            }
+            Instr::Jmp(val) => Flow::Jump(*val as i64),
+            Instr::Nop(_) => Flow::Next,
+        }
+    }
+}
+
 impl Instr {
+    /// Index of the instruction that runs after this one at `index`
+    fn next(&self, index: usize) -> i64 {
+        match self {
+            Instr::Jmp(val) => index as i64 + *val as i64,
+            Instr::Acc(_) | Instr::Nop(_) => index as i64 + 1,
+        }
+    }
+
     fn input() -> Vec<Self> {
         include_str!("../inputs/day08.txt")
             .lines()
@@ -51,57 +73,117 @@ trait Run {
 }
 impl Run for Instrs {
     fn run(&self) -> InstrsExitCode {
-        let mut p: usize = 0;
-        let mut acc = 0;
-        let mut seen: HashSet<usize> = HashSet::new();
-        loop {
-            seen.insert(p);
-
-            match self[p] {
-                Instr::Acc(val) => {
-                    acc += val;
-                    p += 1;
-                }
-                Instr::Jmp(val) => p = p.checked_add_signed(val as isize).expect("jmp ovrflw"),
-                Instr::Nop(_) => p += 1,
+        let mut machine = Machine::new(self.clone()).with_loop_detection(LoopDetection::Pc);
+        let exit = machine.run();
+        let acc = machine.registers.get("acc") as i32;
+        match exit {
+            Exit::Terminated { .. } => InstrsExitCode::Succ(acc),
+            Exit::Loop { .. } => InstrsExitCode::InfLoop(acc),
+            Exit::Halted { .. } | Exit::StepLimit => {
+                unreachable!("console programs have no halt instruction and no step limit")
             }
+        }
+    }
+}
+
+/// Rewrites a single instruction, `None` when it doesn't apply
+type Mutation = fn(&Instr) -> Option<Instr>;
+
+fn swap_jmp_nop(instr: &Instr) -> Option<Instr> {
+    match instr {
+        Instr::Jmp(val) => Some(Instr::Nop(*val)),
+        Instr::Nop(val) => Some(Instr::Jmp(*val)),
+        Instr::Acc(_) => None,
+    }
+}
+
+#[derive(Debug)]
+#[allow(dead_code)]
+struct Patch {
+    index: usize,
+    replacement: Instr,
+    acc: i32,
+}
 
-            if p >= self.len() {
-                return InstrsExitCode::Succ(acc);
+/// Finds the one mutated instruction that lets the program run off the end
+///
+/// Works backwards from the end to mark every instruction that already
+/// terminates, then walks the looping path from the start until a mutation
+/// lands on one of them. Only the winning patch is ever executed.
+fn find_patch(instrs: &Instrs, mutations: &[Mutation]) -> Option<Patch> {
+    let end = instrs.len();
+    let mut sources: Vec<Vec<usize>> = vec![Vec::new(); end + 1];
+    for (index, instr) in instrs.iter().enumerate() {
+        if let Ok(next) = usize::try_from(instr.next(index)) {
+            sources[next.min(end)].push(index);
+        }
+    }
+    let mut terminates = vec![false; end + 1];
+    terminates[end] = true;
+    let mut queue = VecDeque::from([end]);
+    while let Some(index) = queue.pop_front() {
+        for &source in &sources[index] {
+            if !terminates[source] {
+                terminates[source] = true;
+                queue.push_back(source);
             }
+        }
+    }
 
-            if seen.contains(&p) {
-                return InstrsExitCode::InfLoop(acc);
+    let lands_on_exit = |index: usize, instr: &Instr| match usize::try_from(instr.next(index)) {
+        Ok(next) => terminates[next.min(end)],
+        Err(_) => false,
+    };
+    let mut visited = vec![false; end];
+    let mut index = 0;
+    while index < end && !visited[index] {
+        visited[index] = true;
+        for mutation in mutations {
+            let Some(replacement) = mutation(&instrs[index]) else {
+                continue;
+            };
+            if lands_on_exit(index, &replacement) {
+                let mut patched = instrs.clone();
+                patched[index] = replacement;
+                return match patched.run() {
+                    InstrsExitCode::Succ(acc) => Some(Patch {
+                        index,
+                        replacement,
+                        acc,
+                    }),
+                    InstrsExitCode::InfLoop(_) => None,
+                };
             }
         }
+        index = usize::try_from(instrs[index].next(index)).ok()?;
     }
+    None
 }
 
 #[allow(dead_code)]
 pub fn part_two() {
     let start = std::time::Instant::now();
     let instrs = Instr::input();
-    let mut result: Option<InstrsExitCode> = None;
-    for (i, instr) in instrs.iter().enumerate() {
-        match instr {
-            Instr::Acc(_) => continue,
-            Instr::Nop(val) if *val == 0 => continue,
-            instr => {
-                let mut adj_instrs = instrs.clone();
-                adj_instrs[i] = match instr {
-                    Instr::Acc(_) => panic!("oops"), // should never happen
-                    Instr::Jmp(val) => Instr::Nop(*val),
-                    Instr::Nop(val) => Instr::Jmp(*val),
-                };
-                result = Some(adj_instrs.run());
-                match result {
-                    Some(InstrsExitCode::InfLoop(_)) => continue,
-                    Some(InstrsExitCode::Succ(_)) => break,
-                    None => panic!("oops"),
-                }
-            }
-        }
-    }
-    println!("part_two()={:?}", result.expect("result not found"));
+    let patch = find_patch(&instrs, &[swap_jmp_nop]).expect("patch not found");
+    println!("part_two()={:?}", patch);
     println!("runtime={:#?}", start.elapsed());
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn patches_the_example() {
+        let instrs: Instrs =
+            "nop +0\nacc +1\njmp +4\nacc +3\njmp -3\nacc -99\nacc +1\njmp -4\nacc +6"
+                .lines()
+                .map(Instr::from)
+                .collect();
+        let patch = find_patch(&instrs, &[swap_jmp_nop]).unwrap();
+        assert_eq!(
+            (patch.index, patch.replacement, patch.acc),
+            (7, Instr::Nop(-4), 8)
+        );
+    }
+}
@@ -1,37 +1,102 @@
-use std::collections::HashMap;
+use crate::utils::answer::Answer;
+use crate::utils::bigint::BigUint;
+use crate::utils::matrix::Matrix;
 
-fn fish_map() -> HashMap<usize, usize> {
+/// How many fish there are with each timer value, `0..=NEWBORN`
+type Timers = [u128; NEWBORN + 1];
+
+/// Timer a fish resets to after spawning
+const RESET: usize = 6;
+/// Timer a newly spawned fish starts with
+const NEWBORN: usize = 8;
+
+fn fish_timers() -> Timers {
     include_str!("../inputs/day06.txt")
         .split(",")
-        .fold(HashMap::new(), |mut fish_map, fish| {
-            *fish_map
-                .entry(fish.parse::<usize>().expect("failed to parse to usize"))
-                .or_default() += 1;
-            fish_map
+        .fold([0; NEWBORN + 1], |mut timers, fish| {
+            timers[fish
+                .trim()
+                .parse::<usize>()
+                .expect("failed to parse to usize")] += 1;
+            timers
         })
 }
 
-fn fish_spawning(starting_fish: HashMap<usize, usize>, for_days: usize) -> HashMap<usize, usize> {
-    (0..for_days).fold(starting_fish, |map, _| {
-        let mut new_map: HashMap<usize, usize> = HashMap::new();
-        for (k, v) in map {
-            if let Some(next_k) = k.checked_sub(1) {
-                *new_map.entry(next_k).or_default() += v;
-            } else {
-                *new_map.entry(6).or_default() += v;
-                *new_map.entry(8).or_default() += v;
-            }
-        }
-        new_map
-    })
+/// One day of spawning as a linear map on timer counts
+///
+/// Row `i` says where tomorrow's fish with timer `i` come from: timer `i + 1`
+/// today, plus every fish at `0` for the reset and newborn timers.
+fn transition(reset: usize, newborn: usize) -> Matrix<u128> {
+    let mut matrix = Matrix::zero(newborn + 1);
+    for timer in 1..=newborn {
+        matrix.set(timer - 1, timer, 1);
+    }
+    matrix.set(reset, 0, 1);
+    matrix.set(newborn, 0, 1);
+    matrix
+}
+
+fn population_with(timers: &Timers, days: u64, modulus: Option<u128>) -> Option<u128> {
+    let counts = transition(RESET, NEWBORN)
+        .pow(days, modulus)?
+        .apply(timers, modulus)?;
+    counts
+        .into_iter()
+        .try_fold(0_u128, |total, count| match modulus {
+            Some(modulus) => Some((total + count) % modulus),
+            None => total.checked_add(count),
+        })
+}
+
+/// Exact number of fish after `days`, `None` once it no longer fits in a `u128`
+fn population(timers: &Timers, days: u64) -> Option<u128> {
+    population_with(timers, days, None)
+}
+
+/// Number of fish after `days` modulo `modulus`, fine for `days` up to `u64::MAX`
+#[allow(dead_code)]
+fn population_mod(timers: &Timers, days: u64, modulus: u64) -> u64 {
+    population_with(timers, days, Some(modulus.into())).expect("reduced counts fit") as u64
+}
+
+/// Most days [`population_big`] will count exactly, about 4000 digits of fish
+const EXACT_DAY_LIMIT: u64 = 100_000;
+
+/// Exact number of fish after `days`, one day at a time
+///
+/// Only for small `days`: the count gains a digit every 26 days or so, so
+/// no method can write it down for horizons like `10^18`, and fast
+/// exponentiation would only square ever bigger numbers. `None` past
+/// [`EXACT_DAY_LIMIT`]; use [`population_mod`] there instead.
+fn population_big(timers: &Timers, days: u64) -> Option<BigUint> {
+    if days > EXACT_DAY_LIMIT {
+        return None;
+    }
+    let mut counts: Vec<BigUint> = timers.iter().map(|count| BigUint::from(*count)).collect();
+    for _ in 0..days {
+        counts.rotate_left(1);
+        counts[RESET] = &counts[RESET] + &counts[NEWBORN];
+    }
+    Some(counts.into_iter().sum())
+}
+
+/// The fast `u128` answer when it fits, falling back to the exact big one
+///
+/// The fallback steps one day at a time, so like [`population_big`] this
+/// gives `None` once `days` passes [`EXACT_DAY_LIMIT`].
+fn population_answer(timers: &Timers, days: u64) -> Option<Answer> {
+    match population(timers, days) {
+        Some(count) => Some(Answer::from(count)),
+        None => population_big(timers, days).map(Answer::from),
+    }
 }
 
 #[allow(dead_code)]
 pub fn part_one() {
     let start = std::time::Instant::now();
     println!(
-        "part_one={:?} ... runtime={:?}",
-        fish_spawning(fish_map(), 80).values().sum::<usize>(),
+        "part_one={} ... runtime={:?}",
+        population_answer(&fish_timers(), 80).expect("too many days to count exactly"),
         start.elapsed()
     );
 }
@@ -40,11 +105,45 @@ pub fn part_one() {
 pub fn part_two() {
     let start = std::time::Instant::now();
     println!(
-        "part_one={:?} ... runtime={:?}",
-        fish_spawning(fish_map(), 256).values().sum::<usize>(),
+        "part_one={} ... runtime={:?}",
+        population_answer(&fish_timers(), 256).expect("too many days to count exactly"),
         start.elapsed()
     );
 }
 
 #[cfg(test)]
-mod tests {}
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_the_example_school() {
+        let timers: Timers = [0, 1, 1, 2, 1, 0, 0, 0, 0];
+        assert_eq!(population(&timers, 18), Some(26));
+        assert_eq!(population(&timers, 80), Some(5934));
+        assert_eq!(population(&timers, 256), Some(26984457539));
+        assert_eq!(
+            population_mod(&timers, 256, 1_000_000_007),
+            26984457539 % 1_000_000_007
+        );
+        assert_eq!(population(&timers, 1_000_000_000_000_000_000), None);
+        assert!(population_mod(&timers, 1_000_000_000_000_000_000, 1_000_000_007) < 1_000_000_007);
+        assert_eq!(
+            population_answer(&timers, 256),
+            Some(Answer::Unsigned(26984457539))
+        );
+        assert_eq!(
+            population_big(&timers, 256),
+            Some(BigUint::from(26984457539_u64))
+        );
+        let big = population_big(&timers, 1000).unwrap();
+        assert!(matches!(
+            population_answer(&timers, 1000),
+            Some(Answer::Big(_))
+        ));
+        assert_eq!(
+            population_mod(&timers, 1000, 1_000_000_007).to_string(),
+            (&big % &BigUint::from(1_000_000_007_u64)).to_string()
+        );
+        assert_eq!(population_answer(&timers, 1_000_000_000_000_000_000), None);
+    }
+}
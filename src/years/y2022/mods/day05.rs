@@ -1,6 +1,8 @@
 #[allow(unused_imports)]
 use std::collections::{HashMap, VecDeque};
 
+use crate::utils::parse::{paragraphs, parse_lines, scan};
+
 #[allow(dead_code)]
 fn krates() -> HashMap<usize, VecDeque<String>> {
     include_str!("../inputs/day05.txt")
@@ -64,22 +66,16 @@ fn top_krates(krates: &HashMap<usize, VecDeque<String>>) -> String {
 
 #[allow(dead_code)]
 fn instructions() -> Vec<Instr> {
-    include_str!("../inputs/day05.txt")
-        .lines()
-        .skip(10)
-        .map(|x| {
-            let instr_components = x
-                .split_whitespace()
-                .filter_map(|y| y.parse::<usize>().ok())
-                .collect::<Vec<usize>>();
-            let (quantity, from, to) = (
-                instr_components[0],
-                instr_components[1],
-                instr_components[2],
-            );
-            Instr::new(quantity, from, to)
-        })
-        .collect()
+    let input = include_str!("../inputs/day05.txt");
+    let procedure = paragraphs(input)
+        .into_iter()
+        .nth(1)
+        .expect("rearrangement procedure not found");
+    parse_lines(procedure, |line| {
+        let (quantity, from, to) = scan("move {} from {} to {}", line)?;
+        Ok(Instr::new(quantity, from, to))
+    })
+    .expect("failed to parse instructions")
 }
 
 #[allow(dead_code)]
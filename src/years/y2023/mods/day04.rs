@@ -1,3 +1,5 @@
+use crate::utils::parse::{key_values, parse_lines, scan, unsigned_ints};
+
 #[allow(dead_code)]
 #[derive(Debug, Clone)]
 struct Scratcher {
@@ -18,45 +20,18 @@ impl Scratcher {
 
     #[allow(dead_code)]
     fn input() -> Vec<Self> {
-        include_str!("../inputs/day04.txt").lines().fold(
-            Vec::new(),
-            |mut scratchers: Vec<Scratcher>, line| {
-                let id: u32 = line
-                    .split(':')
-                    .next()
-                    .unwrap()
-                    .split_whitespace()
-                    .nth(1)
-                    .unwrap()
-                    .parse()
-                    .unwrap();
-
-                let win_nums: Vec<u32> = line
-                    .split(':')
-                    .nth(1)
-                    .unwrap()
-                    .split('|')
-                    .next()
-                    .unwrap()
-                    .split_whitespace()
-                    .map(|x| x.parse().unwrap())
-                    .collect();
-
-                let play_nums: Vec<u32> = line
-                    .split(':')
-                    .nth(1)
-                    .unwrap()
-                    .split('|')
-                    .nth(1)
-                    .unwrap()
-                    .split_whitespace()
-                    .map(|x| x.parse().unwrap())
-                    .collect();
-
-                scratchers.push(Self::new(id, win_nums, play_nums));
-                scratchers
-            },
-        )
+        parse_lines(include_str!("../inputs/day04.txt"), |line| {
+            // line: "Card   1: 41 48 83 | 83 86  6 31"
+            let (card, numbers) = key_values(line, ":")?;
+            let (win_nums, play_nums) = key_values(numbers, "|")?;
+            let (id,) = scan("Card {}", card)?;
+            Ok(Self::new(
+                id,
+                unsigned_ints(win_nums)?,
+                unsigned_ints(play_nums)?,
+            ))
+        })
+        .expect("failed to parse scratchers")
     }
 
     #[allow(dead_code)]
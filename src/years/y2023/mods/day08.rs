@@ -1,3 +1,4 @@
+use crate::utils::math::lcm_all;
 use std::collections::{hash_map::Entry, HashMap, VecDeque};
 
 #[derive(Debug)]
@@ -90,39 +91,6 @@ pub fn part_one() {
     println!("runtime={:.2?}", start.elapsed());
 }
 
-fn gcd(num1: &usize, num2: &usize) -> usize {
-    if *num1 == 0 && *num2 == 0 {
-        return 0;
-    }
-
-    let mut a = *num1;
-    let mut b = *num2;
-
-    while b != 0 {
-        let temp = a;
-        a = b;
-        b = temp % b;
-    }
-
-    return a;
-}
-
-fn lcm(num1: usize, num2: usize) -> usize {
-    return num1 * num2 / gcd(&num1, &num2);
-}
-
-fn lcm_vec(nums: Vec<usize>) -> Option<usize> {
-    if nums.is_empty() {
-        None
-    } else {
-        let mut result = nums[0];
-        for num in nums.into_iter().skip(1) {
-            result = lcm(result, num);
-        }
-        Some(result)
-    }
-}
-
 #[allow(dead_code)]
 pub fn part_two() {
     let start = std::time::Instant::now();
@@ -184,8 +152,7 @@ pub fn part_two() {
 
     println!(
         "part_two={:#?}",
-        lcm_vec(ghost_freqs.into_iter().map(|x| x.1).collect::<Vec<usize>>())
-            .expect("lcm not found")
+        lcm_all(ghost_freqs.into_values()).expect("lcm not found")
     );
     println!("runtime={:.2?}", start.elapsed());
 }
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::io::{self, Write};
 
 /// Represents a validated position on the grid
 #[derive(Debug, Clone, Eq, Hash, PartialEq)]
@@ -7,15 +7,8 @@ struct Coordinate {
     col: usize,
 }
 
-/// Represents a position that hasn't been bounds-checked yet
-#[allow(dead_code)]
-struct UncheckedCoordinate {
-    row: i32,
-    col: i32,
-}
-
 /// Represents the four possible directions the guard can face/move
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 enum Direction {
     Left,
     Right,
@@ -24,27 +17,31 @@ enum Direction {
 }
 
 impl Direction {
-    /// Returns the new direction after turning 90 degrees right
-    #[allow(dead_code)]
-    fn turn_right(current_dir: &Direction) -> Direction {
-        match current_dir {
-            Direction::Left => Direction::Up,
-            Direction::Right => Direction::Down,
-            Direction::Up => Direction::Right,
-            Direction::Down => Direction::Left,
+    /// Clockwise from up, so turning right is `+ 1` modulo 4
+    const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Right,
+        Direction::Down,
+        Direction::Left,
+    ];
+
+    fn index(&self) -> usize {
+        match self {
+            Direction::Up => 0,
+            Direction::Right => 1,
+            Direction::Down => 2,
+            Direction::Left => 3,
         }
     }
 }
 
+/// The guard's starting point; they always start out facing up
 #[derive(Clone)]
-#[allow(dead_code)]
 struct Guard {
     pos: Coordinate,
-    dir: Direction,
 }
 
 /// Represents a grid where a guard patrols and tracks their movement
-#[allow(dead_code)]
 struct Grid {
     tiles: Vec<Vec<char>>,
     rows: usize,
@@ -55,12 +52,13 @@ struct Grid {
 impl Grid {
     #[allow(dead_code)]
     fn new() -> Self {
-        // Read the input file and convert each line into a vector of characters
+        Grid::parse(include_str!("../inputs/day06.txt"))
+    }
+
+    fn parse(input: &str) -> Self {
+        // Convert each line into a vector of characters
         // Each line becomes a row, and each character becomes a column element
-        let tiles: Vec<Vec<char>> = include_str!("../inputs/day06.txt")
-            .lines()
-            .map(|line| line.chars().collect())
-            .collect();
+        let tiles: Vec<Vec<char>> = input.lines().map(|line| line.chars().collect()).collect();
 
         // Store grid dimensions for boundary checking later during guard movement
         let rows = tiles.len();
@@ -77,16 +75,10 @@ impl Grid {
             }
         }
 
-        // The guard always starts facing upward according to puzzle rules
-        let dir = Some(Direction::Up);
-
-        // Construct the guard with its initial position and direction
+        // Construct the guard with its initial position
         // We can safely unwrap since the input is guaranteed to contain
         // exactly one guard marked by '^'
-        let guard = Some(Guard {
-            pos: pos.unwrap(),
-            dir: dir.unwrap(),
-        });
+        let guard = Some(Guard { pos: pos.unwrap() });
 
         // Return the fully initialized grid with the guard in place
         Grid {
@@ -96,135 +88,350 @@ impl Grid {
             guard,
         }
     }
+}
 
-    /// Calculates the guard's next position and direction based on current state
-    #[allow(dead_code)]
-    fn next_guard(&self) -> Option<Guard> {
-        let current_guard = self.guard.as_ref()?; // Early return if guard is None
-
-        // Calculate potential next position based on current direction
-        let next_pos: UncheckedCoordinate = match current_guard.dir {
-            Direction::Left => UncheckedCoordinate {
-                row: current_guard.pos.row as i32,
-                col: current_guard.pos.col as i32 - 1,
-            },
-            Direction::Right => UncheckedCoordinate {
-                row: current_guard.pos.row as i32,
-                col: current_guard.pos.col as i32 + 1,
-            },
-            Direction::Up => UncheckedCoordinate {
-                row: current_guard.pos.row as i32 - 1,
-                col: current_guard.pos.col as i32,
-            },
-            Direction::Down => UncheckedCoordinate {
-                row: current_guard.pos.row as i32 + 1,
-                col: current_guard.pos.col as i32,
-            },
-        };
-
-        // Return None if next position is out of bounds
-        if next_pos.row < 0
-            || next_pos.col < 0
-            || next_pos.row >= self.rows as i32
-            || next_pos.col >= self.cols as i32
-        {
-            return None;
+/// Cell index used by [`Patrol`] for "the guard walked off the map"
+const EXIT: usize = usize::MAX;
+
+/// Fixed-size set of small integers, one bit each
+struct BitSet {
+    words: Vec<u64>,
+}
+
+impl BitSet {
+    fn new(size: usize) -> Self {
+        BitSet {
+            words: vec![0; size.div_ceil(64)],
         }
+    }
 
-        // Convert checked position to regular Coordinate
-        let next_pos = Coordinate {
-            row: next_pos.row as usize,
-            col: next_pos.col as usize,
-        };
-
-        // Handle collision with obstacle (#)
-        let next_char = self.tiles[next_pos.row][next_pos.col];
-        if next_char == '#' {
-            // Return turned guard
-            Some(Guard {
-                pos: current_guard.pos.clone(),
-                dir: Direction::turn_right(&current_guard.dir),
-            })
-        } else {
-            // Return moved guard
-            Some(Guard {
-                pos: next_pos,
-                dir: current_guard.dir.clone(),
-            })
+    /// Returns `false` if `value` was already present
+    fn insert(&mut self, value: usize) -> bool {
+        let (word, bit) = (value / 64, 1 << (value % 64));
+        let fresh = self.words[word] & bit == 0;
+        self.words[word] |= bit;
+        fresh
+    }
+
+    fn clear(&mut self) {
+        self.words.fill(0);
+    }
+}
+
+/// The grid flattened to cell indices, with precomputed straight-line walks
+///
+/// `jumps[dir][cell]` is the cell the guard ends up in when walking from
+/// `cell` in `dir` until the next obstacle, or [`EXIT`] if nothing stops
+/// them before the edge. Following jumps visits only the turning points,
+/// so checking whether an extra obstacle causes a loop takes a few hundred
+/// lookups rather than a walk across the whole map.
+struct Patrol {
+    rows: usize,
+    cols: usize,
+    blocked: Vec<bool>,
+    start: usize,
+    jumps: [Vec<usize>; 4],
+}
+
+impl Patrol {
+    fn new(grid: &Grid) -> Self {
+        let (rows, cols) = (grid.rows, grid.cols);
+        let blocked: Vec<bool> = grid
+            .tiles
+            .iter()
+            .flat_map(|row| row.iter().map(|tile| *tile == '#'))
+            .collect();
+        let guard = grid.guard.as_ref().expect("guard not found");
+        let start = guard.pos.row * cols + guard.pos.col;
+
+        let mut jumps = [
+            vec![EXIT; rows * cols],
+            vec![EXIT; rows * cols],
+            vec![EXIT; rows * cols],
+            vec![EXIT; rows * cols],
+        ];
+        for col in 0..cols {
+            let mut stop = EXIT;
+            for row in 0..rows {
+                let cell = row * cols + col;
+                if blocked[cell] {
+                    stop = cell + cols;
+                } else {
+                    jumps[Direction::Up.index()][cell] = stop;
+                }
+            }
+            let mut stop = EXIT;
+            for row in (0..rows).rev() {
+                let cell = row * cols + col;
+                if blocked[cell] {
+                    stop = cell.wrapping_sub(cols);
+                } else {
+                    jumps[Direction::Down.index()][cell] = stop;
+                }
+            }
+        }
+        for row in 0..rows {
+            let mut stop = EXIT;
+            for col in 0..cols {
+                let cell = row * cols + col;
+                if blocked[cell] {
+                    stop = cell + 1;
+                } else {
+                    jumps[Direction::Left.index()][cell] = stop;
+                }
+            }
+            let mut stop = EXIT;
+            for col in (0..cols).rev() {
+                let cell = row * cols + col;
+                if blocked[cell] {
+                    stop = cell.wrapping_sub(1);
+                } else {
+                    jumps[Direction::Right.index()][cell] = stop;
+                }
+            }
+        }
+
+        Patrol {
+            rows,
+            cols,
+            blocked,
+            start,
+            jumps,
         }
     }
 
-    /// Simulates a guard's movement through a grid and counts unique positions visited
-    ///
-    /// Takes ownership of a Grid and returns a tuple containing:
-    /// - A HashMap mapping visited coordinates to the directions faced at that coordinate
-    /// - A boolean indicating if simulation ended due to infinite loop (true) or moving off grid (false)
-    ///
-    /// The simulation ends when either:
-    /// - The guard moves off the grid
-    /// - An infinite loop is detected (guard visits same position facing same direction)
-    #[allow(dead_code)]
-    fn simulate_guard(mut grid: Grid) -> (HashMap<Coordinate, Vec<Direction>>, bool) {
-        let mut visited: HashMap<Coordinate, Vec<Direction>> = HashMap::new();
+    /// Next cell in `dir`, `None` past the edge
+    fn neighbour(&self, cell: usize, dir: usize) -> Option<usize> {
+        let (row, col) = (cell / self.cols, cell % self.cols);
+        match Direction::ALL[dir] {
+            Direction::Up => (row > 0).then(|| cell - self.cols),
+            Direction::Down => (row + 1 < self.rows).then(|| cell + self.cols),
+            Direction::Left => (col > 0).then(|| cell - 1),
+            Direction::Right => (col + 1 < self.cols).then(|| cell + 1),
+        }
+    }
+
+    /// Every `(cell, direction)` the guard is in, one step at a time, until
+    /// they leave the map (or start repeating themselves)
+    fn walk(&self) -> Vec<(usize, usize)> {
+        self.walk_with(None)
+    }
 
-        loop {
-            // Get the current guard state
-            let current_guard = match &grid.guard {
-                Some(guard) => guard.clone(),
+    /// Same as [`Patrol::walk`] with one extra obstacle on the map
+    fn walk_with(&self, obstacle: Option<usize>) -> Vec<(usize, usize)> {
+        let mut seen = BitSet::new(self.blocked.len() * 4);
+        let (mut cell, mut dir) = (self.start, Direction::Up.index());
+        let mut states = Vec::new();
+        while seen.insert(cell * 4 + dir) {
+            states.push((cell, dir));
+            match self.neighbour(cell, dir) {
                 None => break,
-            };
+                Some(next) if self.blocked[next] || obstacle == Some(next) => dir = (dir + 1) % 4,
+                Some(next) => cell = next,
+            }
+        }
+        states
+    }
 
-            // Exit loop when guard visits same position in same direction
-            // Indicating infinite loop
-            if visited
-                .entry(current_guard.pos.clone())
-                .or_default()
-                .contains(&current_guard.dir)
+    /// Where walking from `cell` in `dir` stops once `obstacle` is added too
+    fn jump_with(&self, cell: usize, dir: usize, obstacle: usize) -> usize {
+        let target = self.jumps[dir][cell];
+        let (row, col) = (cell / self.cols, cell % self.cols);
+        let (o_row, o_col) = (obstacle / self.cols, obstacle % self.cols);
+        let reaches = |ahead: bool, within: bool| ahead && (target == EXIT || within);
+        match Direction::ALL[dir] {
+            Direction::Up if o_col == col && reaches(o_row < row, o_row >= target / self.cols) => {
+                obstacle + self.cols
+            }
+            Direction::Down
+                if o_col == col && reaches(o_row > row, o_row <= target / self.cols) =>
+            {
+                obstacle - self.cols
+            }
+            Direction::Left
+                if o_row == row && reaches(o_col < col, o_col >= target % self.cols) =>
+            {
+                obstacle + 1
+            }
+            Direction::Right
+                if o_row == row && reaches(o_col > col, o_col <= target % self.cols) =>
             {
-                return (visited, true);
+                obstacle - 1
             }
+            _ => target,
+        }
+    }
 
-            // Mark current position as visited
-            visited
-                .entry(current_guard.pos.clone())
-                .or_default()
-                .push(current_guard.dir.clone());
+    /// Whether the guard, standing at `from`, loops forever once `obstacle` is added
+    fn loops_with(&self, obstacle: usize, from: (usize, usize), seen: &mut BitSet) -> bool {
+        seen.clear();
+        let (mut cell, mut dir) = from;
+        while seen.insert(cell * 4 + dir) {
+            cell = self.jump_with(cell, dir, obstacle);
+            if cell == EXIT {
+                return false;
+            }
+            dir = (dir + 1) % 4;
+        }
+        true
+    }
 
-            // Mark current position as blank space
-            grid.tiles[current_guard.pos.row][current_guard.pos.col] = '.';
+    fn visited_cells(&self) -> usize {
+        let mut cells = BitSet::new(self.blocked.len());
+        self.walk()
+            .into_iter()
+            .filter(|(cell, _)| cells.insert(*cell))
+            .count()
+    }
 
-            // Get next position and direction
-            grid.guard = Grid::next_guard(&grid);
+    /// Cells where one new obstacle traps the guard in a loop
+    ///
+    /// Only cells on the original path matter. Each one is tried from the
+    /// state just before the guard first steps onto it, since everything
+    /// up to that point is unchanged by the new obstacle.
+    fn loop_obstructions(&self) -> Vec<usize> {
+        let states = self.walk();
+        let mut tried = BitSet::new(self.blocked.len());
+        tried.insert(self.start);
+        let mut seen = BitSet::new(self.blocked.len() * 4);
+        states
+            .windows(2)
+            .filter(|pair| tried.insert(pair[1].0))
+            .filter(|pair| self.loops_with(pair[1].0, pair[0], &mut seen))
+            .map(|pair| pair[1].0)
+            .collect()
+    }
+}
 
-            // If guard is still on grid, update their position marker
-            if let Some(guard) = &grid.guard {
-                grid.tiles[guard.pos.row][guard.pos.col] = '^';
-            } else {
-                break; // Exit loop when guard moves off grid
-            }
+impl Patrol {
+    /// The map with the walked `states` drawn the way the puzzle does
+    ///
+    /// `|` and `-` for vertical and horizontal moves, `+` where the guard
+    /// went both ways (including turns), `^` for the start and `O` for the
+    /// given obstruction cells.
+    fn render(&self, states: &[(usize, usize)], obstructions: &[usize]) -> String {
+        let mut tiles: Vec<char> = self
+            .blocked
+            .iter()
+            .map(|blocked| if *blocked { '#' } else { '.' })
+            .collect();
+        for (cell, dir) in states {
+            let stroke = match Direction::ALL[*dir] {
+                Direction::Up | Direction::Down => '|',
+                Direction::Left | Direction::Right => '-',
+            };
+            tiles[*cell] = match tiles[*cell] {
+                '.' => stroke,
+                current if current == stroke => stroke,
+                _ => '+',
+            };
+        }
+        tiles[self.start] = '^';
+        for obstruction in obstructions {
+            tiles[*obstruction] = 'O';
+        }
+        tiles
+            .chunks(self.cols)
+            .map(|row| row.iter().collect::<String>())
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    /// Writes the map every `every` steps of the walk, then the final map
+    /// with every loop-causing obstruction marked
+    fn animate(&self, every: usize, mut out: impl Write) -> io::Result<()> {
+        let states = self.walk();
+        for end in (every..states.len()).step_by(every) {
+            writeln!(out, "{}\n", self.render(&states[..end], &[]))?;
+        }
+        writeln!(out, "{}", self.render(&states, &self.loop_obstructions()))?;
+        out.flush()
+    }
+}
+
+const USAGE: &str = "usage: aoc patrol <file> [every] [output]";
+
+/// Entry point for `aoc patrol ...`
+///
+/// Without `every` only the final map is drawn.
+pub fn run(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let (path, every, output) = match args {
+        [path, rest @ ..] if rest.len() <= 2 => (path, rest.first(), rest.get(1)),
+        _ => return Err(USAGE.into()),
+    };
+    let every = match every {
+        Some(every) => every
+            .parse::<usize>()
+            .ok()
+            .filter(|every| *every > 0)
+            .ok_or(USAGE)?,
+        None => usize::MAX,
+    };
+    let text =
+        std::fs::read_to_string(path).map_err(|error| format!("failed to read {path}: {error}"))?;
+    let patrol = Patrol::new(&Grid::parse(&text));
+    match output {
+        Some(output) => {
+            patrol.animate(every, io::BufWriter::new(std::fs::File::create(output)?))?
         }
-        (visited, false)
+        None => patrol.animate(every, io::stdout().lock())?,
     }
+    Ok(())
 }
 
 /// Solves part one: counts the number of unique positions visited by the guard
 #[allow(dead_code)]
 pub fn part_one() {
-    println!("{}", Grid::simulate_guard(Grid::new()).0.len());
+    println!("{}", Patrol::new(&Grid::new()).visited_cells());
 }
 
 #[allow(dead_code)]
 pub fn part_two() {
-    let visited = Grid::simulate_guard(Grid::new()).0;
-    let mut loop_obstacle_positions: Vec<Coordinate> = Vec::new();
-
-    for visited_entry in visited {
-        let pos = visited_entry.0;
-        let mut grid = Grid::new();
-        grid.tiles[pos.row][pos.col] = '#';
-        if Grid::simulate_guard(grid).1 {
-            loop_obstacle_positions.push(pos);
-        }
-    }
+    let loop_obstacle_positions = Patrol::new(&Grid::new()).loop_obstructions();
     println!("{}", loop_obstacle_positions.len());
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+....#.....
+.........#
+..........
+..#.......
+.......#..
+..........
+.#..^.....
+........#.
+#.........
+......#...";
+
+    #[test]
+    fn patrols_the_example() {
+        let patrol = Patrol::new(&Grid::parse(EXAMPLE));
+        assert_eq!(patrol.visited_cells(), 41);
+        let mut obstructions = patrol.loop_obstructions();
+        obstructions.sort();
+        // (6, 3), (7, 6), (7, 7), (8, 1), (8, 3), (9, 7) as (row, col)
+        assert_eq!(obstructions, vec![63, 76, 77, 81, 83, 97]);
+    }
+
+    #[test]
+    fn draws_a_looping_path() {
+        let patrol = Patrol::new(&Grid::parse(EXAMPLE));
+        let states = patrol.walk_with(Some(63));
+        let expected = "\
+....#.....
+....+---+#
+....|...|.
+..#.|...|.
+....|..#|.
+....|...|.
+.#.O^---+.
+........#.
+#.........
+......#...";
+        assert_eq!(patrol.render(&states, &[63]), expected);
+    }
+}
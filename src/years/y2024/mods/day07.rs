@@ -1,145 +1,185 @@
+use crate::utils::expr::{self, Inverse};
+
 #[allow(dead_code)]
 fn input() -> Vec<(Vec<i64>, i64)> {
-    include_str!("../inputs/day07.txt")
-        .lines()
-        .fold(Vec::new(), |mut equations, line| {
-            let mut parts = line.split(':');
-            let target = parts.next().unwrap().parse().unwrap();
-            let numbers = parts
-                .next()
-                .unwrap()
-                .split_whitespace()
-                .map(|number| number.parse().unwrap())
-                .collect();
-            equations.push((numbers, target));
-            equations
-        })
+    parse(include_str!("../inputs/day07.txt"))
+}
+
+fn parse(text: &str) -> Vec<(Vec<i64>, i64)> {
+    text.lines().fold(Vec::new(), |mut equations, line| {
+        let mut parts = line.split(':');
+        let target = parts.next().unwrap().parse().unwrap();
+        let numbers = parts
+            .next()
+            .unwrap()
+            .split_whitespace()
+            .map(|number| number.parse().unwrap())
+            .collect();
+        equations.push((numbers, target));
+        equations
+    })
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Operator {
     Add,
     Multiply,
     Concatenate,
 }
 
-#[allow(dead_code)]
-fn numbers_to_operators(nums: &Vec<usize>) -> Vec<Operator> {
-    nums.iter()
-        .map(|char| match char {
-            0 => Operator::Add,
-            1 => Operator::Multiply,
-            2 => Operator::Concatenate,
-            _ => panic!("Error converting {char} to operator"),
-        })
-        .collect()
+/// Smallest power of ten above `number`, so `a * shift(b) + b` concatenates
+fn shift(number: i64) -> Option<i64> {
+    10_i64.checked_pow(number.checked_ilog10().unwrap_or(0) + 1)
 }
 
-#[allow(dead_code)]
-fn do_operation(num1: i64, num2: i64, operator: Operator) -> i64 {
-    match operator {
-        Operator::Add => num1 + num2,
-        Operator::Multiply => num1 * num2,
-        Operator::Concatenate => (num1.to_string() + num2.to_string().as_str())
-            .parse()
-            .unwrap(),
+impl expr::Operator for Operator {
+    fn symbol(&self) -> &'static str {
+        match self {
+            Operator::Add => "+",
+            Operator::Multiply => "*",
+            Operator::Concatenate => "||",
+        }
     }
-}
 
-// Kept for reference: Shows binary pattern representation
-// Note: numbers_to_operators() has been updated and is now incompatible here
-//
-// #[allow(dead_code)]
-// fn operator_combinations_binary_method(numbers: &Vec<i64>) -> Vec<Vec<Operator>> {
-//     let position_count = numbers.len() - 1;
-//     let combination_count = 2_i32.pow(position_count as u32);
-//     let operator_combinations = (0..combination_count)
-//         .map(|i| numbers_to_operators(format!("{i:0position_count$b}")))
-//         .collect();
-//     operator_combinations
-// }
-
-#[allow(dead_code)]
-fn all_combinations(
-    position_count: usize,
-    element_count: usize,
-    current: &mut Vec<usize>,
-) -> Vec<Vec<usize>> {
-    let mut combinations: Vec<Vec<usize>> = Vec::new();
-
-    if position_count == 0 {
-        combinations.push(current.clone());
-        return combinations;
+    fn apply(&self, num1: i64, num2: i64) -> Option<i64> {
+        match self {
+            Operator::Add => num1.checked_add(num2),
+            Operator::Multiply => num1.checked_mul(num2),
+            Operator::Concatenate => num1.checked_mul(shift(num2)?)?.checked_add(num2),
+        }
     }
 
-    for i in 0..element_count {
-        current.push(i);
-        combinations.extend(all_combinations(position_count - 1, element_count, current));
-        current.remove(current.len() - 1);
+    /// `Impossible` when no non-negative left operand works: `result`
+    /// smaller than `num2` for add, not divisible for multiply, not ending in
+    /// the digits of `num2` for concatenate.
+    fn invert(&self, result: i64, num2: i64) -> Inverse {
+        match self {
+            Operator::Add => result.checked_sub(num2).filter(|num1| *num1 >= 0).into(),
+            Operator::Multiply if num2 == 0 && result == 0 => Inverse::Any,
+            Operator::Multiply => (num2 != 0 && result % num2 == 0)
+                .then(|| result / num2)
+                .into(),
+            Operator::Concatenate => shift(num2)
+                .filter(|shift| result % shift == num2)
+                .map(|shift| result / shift)
+                .into(),
+        }
     }
 
-    combinations
+    /// Only matters outside the puzzle, which is always left to right
+    fn precedence(&self) -> u8 {
+        match self {
+            Operator::Concatenate => 0,
+            Operator::Add => 1,
+            Operator::Multiply => 2,
+        }
+    }
 }
 
-#[allow(dead_code)]
-fn operator_combinations(numbers: &Vec<i64>, operators: &Vec<Operator>) -> Vec<Vec<Operator>> {
-    let position_count = numbers.len() - 1;
-    let operator_count = operators.len();
+/// Operators that make `numbers`, evaluated left to right, equal `target`
+///
+/// Works backwards from the target: the last number can only have been
+/// applied by operators that `invert` cleanly, which prunes almost every
+/// branch long before reaching the first number. Assumes the numbers are
+/// non-negative, as they are in the puzzle.
+fn solve<O: expr::Operator>(numbers: &[i64], target: i64, operators: &[O]) -> Option<Vec<O>> {
+    let (last, rest) = numbers.split_last()?;
+    if rest.is_empty() {
+        return (*last == target).then(Vec::new);
+    }
+    operators.iter().find_map(|operator| {
+        let mut solution = match operator.invert(target, *last) {
+            Inverse::Exactly(previous) => solve(rest, previous, operators)?,
+            // e.g. times zero: whatever the rest evaluates to will do
+            Inverse::Any => evaluable(rest[0], &rest[1..], operators)?,
+            Inverse::Impossible => return None,
+        };
+        solution.push(*operator);
+        Some(solution)
+    })
+}
 
-    all_combinations(position_count, operator_count, &mut vec![])
-        .iter()
-        .map(|combination| numbers_to_operators(combination))
-        .collect()
+/// Any operators that evaluate `first` followed by `rest` without overflow
+fn evaluable<O: expr::Operator>(first: i64, rest: &[i64], operators: &[O]) -> Option<Vec<O>> {
+    let Some((next, rest)) = rest.split_first() else {
+        return Some(Vec::new());
+    };
+    operators.iter().find_map(|operator| {
+        let value = operator.apply(first, *next)?;
+        let mut solution = vec![*operator];
+        solution.extend(evaluable(value, rest, operators)?);
+        Some(solution)
+    })
 }
 
+/// `81 + 40 * 27` style rendering of a solution
 #[allow(dead_code)]
-fn numbers_reach_target(numbers: &Vec<i64>, target: &i64, operators: &Vec<Operator>) -> bool {
-    let operator_combinations = operator_combinations(&numbers, operators);
-    let mut result = false;
-
-    for combination in operator_combinations {
-        let mut total: i64 = numbers[0];
-        // We skip the first number since it's already in total
-        // Then we zip the remaining numbers with operators to evaluate them in sequence
-        // For example, with numbers [1,2,3,4] and operators [*,+,||]:
-        //
-        // total = 1
-        // total = 1 * 2  (first operation)
-        // total = 2 + 3  (second operation)
-        // total = 5 || 4 (third operation)
-        for (number, operator) in numbers.iter().skip(1).zip(combination) {
-            total = do_operation(total, *number, operator)
-        }
-
-        if total == *target {
-            result = true;
-            break;
-        }
+fn expression<O: expr::Operator>(numbers: &[i64], operators: &[O]) -> String {
+    let mut text = numbers[0].to_string();
+    for (number, operator) in numbers.iter().skip(1).zip(operators) {
+        text.push_str(&format!(" {} {number}", operator.symbol()));
     }
-    result
+    text
+}
+
+fn calibration_total(equations: &[(Vec<i64>, i64)], operators: &[Operator]) -> i64 {
+    equations
+        .iter()
+        .filter(|(numbers, target)| solve(numbers, *target, operators).is_some())
+        .map(|(_, target)| target)
+        .sum()
 }
 
 #[allow(dead_code)]
 pub fn part_one() {
     let operators = vec![Operator::Add, Operator::Multiply];
-    let equations = input();
-    let mut total: i64 = 0;
-    for (numbers, target) in equations.iter() {
-        if numbers_reach_target(numbers, target, &operators) {
-            total += target;
-        }
-    }
+    let total = calibration_total(&input(), &operators);
     println!("total: {total}");
 }
 #[allow(dead_code)]
 pub fn part_two() {
     let operators = vec![Operator::Add, Operator::Multiply, Operator::Concatenate];
-    let equations = input();
-    let mut total: i64 = 0;
-    for (numbers, target) in equations.iter() {
-        if numbers_reach_target(numbers, target, &operators) {
-            total += target;
-        }
-    }
+    let total = calibration_total(&input(), &operators);
     println!("total: {total}");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "190: 10 19
+3267: 81 40 27
+83: 17 5
+156: 15 6
+7290: 6 8 6 15
+161011: 16 10 13
+192: 17 8 14
+21037: 9 7 18 13
+292: 11 6 16 20";
+
+    #[test]
+    fn solves_the_example_with_witnesses() {
+        let equations = parse(EXAMPLE);
+        let all = [Operator::Add, Operator::Multiply, Operator::Concatenate];
+        assert_eq!(calibration_total(&equations, &all[..2]), 3749);
+        assert_eq!(calibration_total(&equations, &all), 11387);
+
+        let numbers = [6, 8, 6, 15];
+        let solution = solve(&numbers, 7290, &all).unwrap();
+        assert_eq!(expression(&numbers, &solution), "6 * 8 || 6 * 15");
+        let text = expression(&numbers, &solution);
+        assert_eq!(
+            expr::evaluate(&text, &all, expr::Mode::LeftToRight).unwrap(),
+            7290
+        );
+
+        // anything times zero is zero, whatever comes before
+        let add_mul = [Operator::Add, Operator::Multiply];
+        assert_eq!(solve(&[5, 0], 0, &add_mul), Some(vec![Operator::Multiply]));
+        assert_eq!(
+            solve(&[3, 4, 0], 0, &add_mul),
+            Some(vec![Operator::Add, Operator::Multiply])
+        );
+        assert_eq!(solve(&[5, 0], 1, &add_mul), None);
+    }
+}
@@ -1,141 +1,205 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::io::{self, Write};
+
 const INPUT_FILE_PATH: &str = "src/years/y2024/inputs/day09.txt";
 
 fn input_to_string() -> String {
     std::fs::read_to_string(INPUT_FILE_PATH).expect(&format!("File not found at {INPUT_FILE_PATH}"))
 }
 
-#[allow(dead_code)]
+/// A run of blocks that all belong to file `id`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 struct File {
     id: u64,
     location: usize,
     size: usize,
 }
 
-#[derive(Clone)]
-struct Disk {
-    files: Vec<Option<u64>>,
+/// A run of free blocks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Span {
+    location: usize,
+    size: usize,
 }
 
-impl Disk {
-    #[allow(dead_code)]
-    fn new(input: String) -> Self {
-        let mut file_id = 0;
-        input
-            .chars()
-            .enumerate()
-            .fold(Disk { files: Vec::new() }, |mut output, (i, char)| {
-                let size = char.to_digit(10).unwrap() as usize;
-                if i % 2 == 0 {
-                    // file
-                    output.files.extend(vec![
-                        Some(file_id.to_string().parse::<u64>().unwrap());
-                        size
-                    ]);
-                    file_id += 1;
-                } else {
-                    // empty space
-                    output.files.extend(vec![None; size])
-                }
-                output
-            })
-    }
-
-    fn compact_disk_via_fragmenting_files(self) -> Self {
-        let mut new_disk = self.clone();
-
-        while let (Some(free_i), Some(content_i)) = (
-            new_disk.files.iter().position(|element| *element == None),
-            Some(
-                new_disk.files.len()
-                    - new_disk
-                        .files
-                        .iter()
-                        .rev()
-                        .position(|element| element.is_some())
-                        .unwrap()
-                    - 1,
-            ),
-        ) {
-            new_disk.files[free_i] = new_disk.files[content_i]; // place content into free space
-            new_disk.files.remove(content_i); // remove content
-        }
-        new_disk
-    }
-
-    #[allow(dead_code)]
-    fn find_file_from_right(&self, start_at: &usize) -> Option<(u64, usize, usize)> {
-        // Find the rightmost file up to start_at position
-        let position = self.files[..=*start_at]
-            .iter()
-            .rposition(|element| element.is_some())?;
-
-        // Get the file ID
-        let file_id = self.files[position].unwrap();
-
-        // Find the start of this file by scanning left until we find a different value
-        let start = (0..=position)
-            .rev()
-            .find(|&i| self.files[i] != Some(file_id))
-            .map_or(0, |i| i + 1);
+/// `size` blocks of file `id` moved from `from` to `to`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Move {
+    id: u64,
+    from: usize,
+    to: usize,
+    size: usize,
+}
 
-        // Calculate the size by scanning right until we find a different value
-        let size = self.files[start..]
-            .iter()
-            .take_while(|&&x| x == Some(file_id))
-            .count();
+/// Disk as runs of file and free blocks rather than one entry per block
+///
+/// A fragmented file is simply several `File` runs with the same id.
+#[derive(Debug, Clone)]
+struct SpanDisk {
+    files: Vec<File>,
+    free: Vec<Span>,
+    len: usize,
+}
 
-        Some((file_id, start, size))
+impl SpanDisk {
+    fn new(input: &str) -> Self {
+        let mut disk = SpanDisk {
+            files: Vec::new(),
+            free: Vec::new(),
+            len: 0,
+        };
+        for (i, char) in input.trim().chars().enumerate() {
+            let size = char.to_digit(10).expect("disk map digit") as usize;
+            if i % 2 == 0 {
+                disk.files.push(File {
+                    id: (i / 2) as u64,
+                    location: disk.len,
+                    size,
+                });
+            } else if size > 0 {
+                disk.free.push(Span {
+                    location: disk.len,
+                    size,
+                });
+            }
+            disk.len += size;
+        }
+        disk
     }
 
-    #[allow(dead_code)]
-    fn find_free_space_of_size(&self, size: usize, up_to_position: usize) -> Option<usize> {
-        self.files[..up_to_position]
-            .windows(size)
-            .position(|window| window.iter().all(|element| element.is_none()))
+    /// Same disk from its file runs, with the free runs worked out from the gaps
+    fn from_files(mut files: Vec<File>, len: usize) -> Self {
+        files.retain(|file| file.size > 0);
+        files.sort_by_key(|file| file.location);
+        let mut free = Vec::new();
+        let mut end = 0;
+        for file in files.iter().chain([&File {
+            id: 0,
+            location: len,
+            size: 0,
+        }]) {
+            if file.location > end {
+                free.push(Span {
+                    location: end,
+                    size: file.location - end,
+                });
+            }
+            end = file.location + file.size;
+        }
+        SpanDisk { files, free, len }
     }
 
-    #[allow(dead_code)]
-    fn compact_disk_via_migrating_files(self) -> Self {
-        let mut start_at = self.files.len() - 1;
-        let mut new_disk: Disk = self.clone();
-
-        // println!("starting with files...\n{}", Disk::files_to_string(&new_disk.files)); // debug
-
-        while let Some((file_id, file_location, file_size)) = self.find_file_from_right(&start_at) {
-            // println!("found file (id: {}, lc: {}, sz: {})", file_id, file_location, file_size); // debug
-            // let file_print = new_disk.files[file_location..file_location + file_size].to_vec(); // debug
-            // println!("showing file: {}", Disk::files_to_string(&file_print)); // debug
-
-            start_at = match file_location.checked_sub(1) {
-                Some(x) => x,
-                None => break,
-            };
-
-            // Only look for free space up to the current file's location
-            if let Some(free_location) = new_disk.find_free_space_of_size(file_size, file_location)
+    /// Moves single blocks from the end of the disk into the leftmost gaps
+    ///
+    /// Walks files from the right and gaps from the left, so every run is
+    /// visited once.
+    fn compact_via_fragmenting_files(&self, mut on_move: impl FnMut(Move)) -> Self {
+        let mut free = self.free.clone();
+        let mut next_free = 0;
+        let mut files = Vec::with_capacity(self.files.len());
+
+        for file in self.files.iter().rev() {
+            let mut remaining = file.size;
+            while remaining > 0
+                && next_free < free.len()
+                && free[next_free].location < file.location
             {
-                // println!("found free space (lc: {})", free_location); // debug
-
-                for i in free_location..free_location + file_size {
-                    new_disk.files[i] = Some(file_id);
+                let gap = &mut free[next_free];
+                let moved = remaining.min(gap.size);
+                // reported block by block, the way the puzzle walks through it
+                for block in 0..moved {
+                    on_move(Move {
+                        id: file.id,
+                        from: file.location + remaining - 1 - block,
+                        to: gap.location + block,
+                        size: 1,
+                    });
                 }
+                files.push(File {
+                    id: file.id,
+                    location: gap.location,
+                    size: moved,
+                });
+                gap.location += moved;
+                gap.size -= moved;
+                remaining -= moved;
+                if gap.size == 0 {
+                    next_free += 1;
+                }
+            }
+            files.push(File {
+                size: remaining,
+                ..*file
+            });
+        }
+        Self::from_files(files, self.len)
+    }
 
-                // println!("cloning file to free location...\n{}", Disk::files_to_string(&new_disk.files)); // debug
+    /// Moves whole files, highest id first, into the leftmost gap that fits
+    ///
+    /// Gaps are kept in one min-heap of locations per gap size, so finding
+    /// the leftmost fit only looks at the smallest location of each size.
+    /// Space freed by a move is never needed again: every file still to be
+    /// moved sits to its left.
+    fn compact_via_migrating_files(&self, mut on_move: impl FnMut(Move)) -> Self {
+        let largest = self.free.iter().map(|gap| gap.size).max().unwrap_or(0);
+        let mut gaps: Vec<BinaryHeap<Reverse<usize>>> = vec![BinaryHeap::new(); largest + 1];
+        for gap in &self.free {
+            gaps[gap.size].push(Reverse(gap.location));
+        }
 
-                for i in file_location..file_location + file_size {
-                    new_disk.files[i] = None;
+        let mut files = self.files.clone();
+        for file in files.iter_mut().rev() {
+            let best = (file.size..=largest)
+                .filter_map(|size| gaps[size].peek().map(|Reverse(location)| (*location, size)))
+                .filter(|(location, _)| *location < file.location)
+                .min();
+            if let Some((location, size)) = best {
+                gaps[size].pop();
+                if size > file.size {
+                    gaps[size - file.size].push(Reverse(location + file.size));
                 }
-                // println!("deleting file at original location...\n{}", Disk::files_to_string(&new_disk.files)); // debug
-                // println!("==="); // debug
+                on_move(Move {
+                    id: file.id,
+                    from: file.location,
+                    to: location,
+                    size: file.size,
+                });
+                file.location = location;
             }
         }
-        Disk {
-            files: new_disk.files,
+        Self::from_files(files, self.len)
+    }
+
+    fn calculate_checksum(&self) -> u64 {
+        self.files
+            .iter()
+            .map(|file| {
+                let (location, size) = (file.location as u64, file.size as u64);
+                // id * (location + (location + 1) + ... + (location + size - 1))
+                file.id * (size * location + size * size.saturating_sub(1) / 2)
+            })
+            .sum()
+    }
+
+    /// One entry per block, for rendering and cross-checking the checksum
+    fn to_blocks(&self) -> Disk {
+        let mut files = vec![None; self.len];
+        for file in &self.files {
+            files[file.location..file.location + file.size].fill(Some(file.id));
         }
+        Disk { files }
     }
+}
 
-    #[allow(dead_code)]
-    fn files_to_string(files: &Vec<Option<u64>>) -> String {
+#[derive(Clone)]
+struct Disk {
+    files: Vec<Option<u64>>,
+}
+
+impl Disk {
+    fn files_to_string(files: &[Option<u64>]) -> String {
         files
             .iter()
             .map(|element| {
@@ -162,13 +226,77 @@ impl Disk {
     }
 }
 
+impl Disk {
+    fn apply(&mut self, step: &Move) {
+        self.files[step.from..step.from + step.size].fill(None);
+        self.files[step.to..step.to + step.size].fill(Some(step.id));
+    }
+}
+
+/// Writes the disk map before compaction, after every `every`th move and
+/// once more at the end if the last move didn't land on a frame
+fn render_moves(
+    disk: &SpanDisk,
+    moves: &[Move],
+    every: usize,
+    mut out: impl Write,
+) -> io::Result<()> {
+    let mut blocks = disk.to_blocks();
+    writeln!(out, "{}", Disk::files_to_string(&blocks.files))?;
+    for (i, step) in moves.iter().enumerate() {
+        blocks.apply(step);
+        if (i + 1) % every == 0 || i + 1 == moves.len() {
+            writeln!(out, "{}", Disk::files_to_string(&blocks.files))?;
+        }
+    }
+    out.flush()
+}
+
+const USAGE: &str = "usage: aoc disk fragment|migrate <file> [every] [output]";
+
+/// Entry point for `aoc disk ...`, replaying a compaction as disk maps
+pub fn run(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let (mode, path, rest) = match args {
+        [mode, path, rest @ ..] if rest.len() <= 2 => (mode.as_str(), path, rest),
+        _ => return Err(USAGE.into()),
+    };
+    let every = match rest.first() {
+        Some(every) => every
+            .parse::<usize>()
+            .ok()
+            .filter(|every| *every > 0)
+            .ok_or(USAGE)?,
+        None => 1,
+    };
+    let text =
+        std::fs::read_to_string(path).map_err(|error| format!("failed to read {path}: {error}"))?;
+    let disk = SpanDisk::new(&text);
+
+    let mut moves = Vec::new();
+    match mode {
+        "fragment" => disk.compact_via_fragmenting_files(|step| moves.push(step)),
+        "migrate" => disk.compact_via_migrating_files(|step| moves.push(step)),
+        _ => return Err(USAGE.into()),
+    };
+    match rest.get(1) {
+        Some(output) => render_moves(
+            &disk,
+            &moves,
+            every,
+            io::BufWriter::new(std::fs::File::create(output)?),
+        )?,
+        None => render_moves(&disk, &moves, every, io::stdout().lock())?,
+    }
+    Ok(())
+}
+
 #[allow(dead_code)]
 pub fn part_one() {
     println!(
         "{}",
-        Disk::calculate_checksum(Disk::compact_disk_via_fragmenting_files(Disk::new(
-            input_to_string()
-        )))
+        SpanDisk::new(&input_to_string())
+            .compact_via_fragmenting_files(|_| ())
+            .calculate_checksum()
     )
 }
 
@@ -176,15 +304,14 @@ pub fn part_one() {
 pub fn part_two() {
     println!(
         "{}",
-        Disk::calculate_checksum(Disk::compact_disk_via_migrating_files(Disk::new(
-            input_to_string()
-        )))
+        SpanDisk::new(&input_to_string())
+            .compact_via_migrating_files(|_| ())
+            .calculate_checksum()
     )
 }
 
 #[cfg(test)]
 mod tests {
-    #[allow(dead_code)]
     fn str_to_files(input: &str) -> Vec<Option<u64>> {
         input
             .chars()
@@ -197,61 +324,57 @@ mod tests {
 
     use super::*;
 
-    #[ignore]
-    #[allow(dead_code)]
+    #[test]
     fn test_decompress_disk() {
-        let test: Disk = Disk::new("12345".to_string());
+        let test = SpanDisk::new("12345").to_blocks();
         let expected: Vec<Option<u64>> = str_to_files("0..111....22222");
 
         assert_eq!(test.files, expected)
     }
 
-    #[ignore]
-    #[allow(dead_code)]
+    #[test]
     fn compact_disk_via_fragmenting_files() {
-        let test: Disk = Disk::compact_disk_via_fragmenting_files(Disk::new("12345".to_string()));
-        let expected = str_to_files("022111222");
+        let test = SpanDisk::new("12345")
+            .compact_via_fragmenting_files(|_| ())
+            .to_blocks();
+        let expected = str_to_files("022111222......");
 
         assert_eq!(test.files, expected)
     }
 
-    #[ignore]
-    #[allow(dead_code)]
+    #[test]
     fn test_calculate_checksum() {
-        let test: u64 = Disk::calculate_checksum(Disk::compact_disk_via_fragmenting_files(
-            Disk::new("12345".to_string()),
-        ));
+        let test = SpanDisk::new("12345").compact_via_fragmenting_files(|_| ());
         let expected: u64 = 60;
 
-        assert_eq!(test, expected)
-    }
-
-    #[ignore]
-    #[allow(dead_code)]
-    fn test_find_free_space_of_size() {
-        let test_disk = Disk::new("12345".to_string());
-        let test = Disk::find_free_space_of_size(&test_disk, 2, test_disk.files.len());
-        let expected = Some(1);
-        assert_eq!(test, expected)
-    }
-
-    #[ignore]
-    #[allow(dead_code)]
-    fn test_find_file_from_right() {
-        let test_disk = Disk::new("12345".to_string());
-        let start_at = test_disk.files.len() - 1;
-        let test = Disk::find_file_from_right(&test_disk, &start_at);
-        let expected = Some((2, 10, 5));
-        assert_eq!(test, expected)
+        assert_eq!(test.calculate_checksum(), expected);
+        assert_eq!(test.to_blocks().calculate_checksum(), expected)
     }
 
-    #[ignore]
-    #[allow(dead_code)]
+    #[test]
     fn test_compact_disk_via_migrating_files() {
-        let test =
-            Disk::compact_disk_via_migrating_files(Disk::new("2333133121414131402".to_string()))
-                .files;
+        let test = SpanDisk::new("2333133121414131402").compact_via_migrating_files(|_| ());
         let expected = str_to_files("00992111777.44.333....5555.6666.....8888..");
-        assert_eq!(test, expected)
+        assert_eq!(test.to_blocks().files, expected);
+        assert_eq!(test.calculate_checksum(), 2858);
+        assert_eq!(test.to_blocks().calculate_checksum(), 2858)
+    }
+
+    #[test]
+    fn renders_the_worked_example() {
+        let disk = SpanDisk::new("12345");
+        let mut moves = Vec::new();
+        disk.compact_via_fragmenting_files(|step| moves.push(step));
+        let mut frames = Vec::new();
+        render_moves(&disk, &moves, 1, &mut frames).unwrap();
+        let expected = "\
+0..111....22222
+02.111....2222.
+022111....222..
+0221112...22...
+02211122..2....
+022111222......
+";
+        assert_eq!(String::from_utf8(frames).unwrap(), expected);
     }
 }
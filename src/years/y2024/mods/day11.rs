@@ -3,12 +3,12 @@ fn even_digited(number: usize) -> bool {
     number.to_string().len() % 2 == 0
 }
 
-use std::collections::HashMap;
+use crate::utils::memo::Memo;
 
 #[allow(dead_code)]
 struct StoneSystem {
     stones: Vec<usize>,
-    cache: HashMap<(usize, usize), usize>,
+    cache: Memo<(usize, usize), usize>,
 }
 
 impl StoneSystem {
@@ -20,7 +20,7 @@ impl StoneSystem {
                 .split_whitespace()
                 .map(|x| x.parse().unwrap())
                 .collect(),
-            cache: HashMap::new(),
+            cache: Memo::new(),
         }
     }
 
@@ -44,8 +44,8 @@ impl StoneSystem {
 
     #[allow(dead_code)]
     fn process_number(&mut self, number: usize, blink_count: usize) -> usize {
-        if self.cache.contains_key(&(number, blink_count)) {
-            return self.cache.get(&(number, blink_count)).unwrap().clone();
+        if let Some(count) = self.cache.get(&(number, blink_count)) {
+            return count;
         }
 
         let result = self.blink_alot_r(blink(number), blink_count - 1);
@@ -80,6 +80,7 @@ pub fn part_one() {
 pub fn part_two() {
     let mut system = StoneSystem::new();
     println!("{}", system.blink_stones(75));
+    println!("cache: {}", system.cache.stats());
 }
 
 #[cfg(test)]